@@ -1,35 +1,58 @@
-mod tiny_shell;
-use tiny_shell::tiny_shell::Shell;
+use rust_shell::tiny_shell::{default_rc_path, highlight, Io, ScriptOptions, Shell, StdTerminal};
 
-fn shell_cmd_help(_argc: Vec<&str>, _argv: usize) {
-    Shell::puts("help\n\rclear\n\recho\n\r");
+fn shell_cmd_clear(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    io.terminal().write(b"\x1b[2J\x1b[H");
+    0
 }
 
-fn shell_cmd_clear(_argc: Vec<&str>, _argv: usize) {
-    Shell::cls();
-}
-
-fn shell_cmd_echo(argc: Vec<&str>, argv: usize) {
-    for i in 1..argv {
-        if i != (argv - 1) {
-            Shell::puts(format!("{} ", argc[i]).as_ref());
-        } else {
-            Shell::puts(format!("{}\n\r", argc[i]).as_ref());
-        }
-    }
+fn shell_cmd_echo(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    writeln!(io, "{}", argc[1..].join(" "));
+    0
 }
 
 fn main() {
-    let mut shell = Shell::new("shell > ");
-    shell.add_command("help", shell_cmd_help);
+    let mut shell = Shell::new("shell > ", StdTerminal::new());
     shell.add_command("clear", shell_cmd_clear);
     shell.add_command("echo", shell_cmd_echo);
+    shell.add_filters();
+    shell.enable_external_commands(true);
+    shell.set_highlighter(Some(highlight));
 
-    shell.start();
-    Shell::puts("type 'help' for help\n\r");
+    /* usage: rust_shell [-v] [-e] [--norc] [script] */
+    let mut opts = ScriptOptions::default();
+    let mut script = None;
+    let mut load_rc = true;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-v" => opts.echo = true,
+            "-e" => opts.stop_on_error = true,
+            "--norc" => load_rc = false,
+            _ => script = Some(arg),
+        }
+    }
 
-    loop {
-        let cmd = shell.listen();
-        shell.parse(cmd.as_str());
+    /* run non-interactively if a script is given or the input is piped */
+    if script.is_some() || !StdTerminal::is_tty() {
+        let status = match script {
+            Some(path) => shell.run_file(&path, opts),
+            None => shell.run_script(std::io::stdin().lock(), opts),
+        };
+
+        std::process::exit(match status {
+            Ok(status) => status,
+            Err(err) => {
+                eprintln!("rust_shell: {}", err);
+                2
+            }
+        });
+    }
+
+    if let Some(rc) = default_rc_path().filter(|_| load_rc) {
+        shell.load_rc(rc);
     }
+
+    Shell::puts("type 'help' for help\n\r");
+
+    let status = shell.run();
+    std::process::exit(status);
 }
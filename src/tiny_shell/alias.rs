@@ -0,0 +1,175 @@
+use super::shell::Shell;
+use super::term::Terminal;
+use super::{Line, List};
+use core::fmt::Write;
+
+/* nesting limit of aliases expanding to other aliases */
+pub(crate) const ALIAS_DEPTH_MAX: usize = 8;
+
+/// Stack of the aliases being expanded
+pub(crate) type AliasStack = List<Line, ALIAS_DEPTH_MAX>;
+
+/// Alias names may hold anything but whitespace and the characters special to the parser
+fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| !c.is_whitespace() && !matches!(c, '\'' | '"' | '\\' | '$' | '=' | '#'))
+}
+
+/* appends `arg` so that the parser reads it back as exactly one word */
+fn push_quoted(out: &mut Line, arg: &str, in_double_quote: bool) {
+    if in_double_quote {
+        for c in arg.chars() {
+            if matches!(c, '"' | '\\' | '$') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    } else {
+        out.push('\'');
+        for c in arg.chars() {
+            if c == '\'' {
+                out.push_str("'\\''");
+            } else {
+                out.push(c);
+            }
+        }
+        out.push('\'');
+    }
+}
+
+/// Substitutes the positional parameters of an alias body.
+///
+/// `$0` is the alias name, `$1` to `$9` its arguments, `$@` all arguments and `$#` their
+/// count. Arguments are quoted so they stay single words when the result is parsed, and
+/// if the body refers to none of them the arguments are appended to it instead.
+pub(crate) fn substitute_positionals(body: &str, argc: &[&str]) -> Line {
+    let mut out = Line::new();
+    let mut used = false;
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+            '"' if !in_single_quote => in_double_quote = !in_double_quote,
+            '\\' if !in_single_quote => {
+                out.push(c);
+                if let Some(c) = chars.next() {
+                    out.push(c);
+                }
+                continue;
+            }
+            '$' if !in_single_quote => match chars.peek() {
+                Some(&d @ '0'..='9') => {
+                    chars.next();
+                    used = true;
+                    if let Some(arg) = argc.get(d as usize - '0' as usize) {
+                        push_quoted(&mut out, arg, in_double_quote);
+                    }
+                    continue;
+                }
+                Some('@') => {
+                    chars.next();
+                    used = true;
+                    for (i, arg) in argc.iter().skip(1).enumerate() {
+                        if i > 0 {
+                            out.push(' ');
+                        }
+                        push_quoted(&mut out, arg, in_double_quote);
+                    }
+                    continue;
+                }
+                Some('#') => {
+                    chars.next();
+                    used = true;
+                    let _ = write!(out, "{}", argc.len().saturating_sub(1));
+                    continue;
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        out.push(c);
+    }
+
+    if !used {
+        for arg in argc.iter().skip(1) {
+            out.push(' ');
+            push_quoted(&mut out, arg, false);
+        }
+    }
+
+    out
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Expands `argc[0]` if it is an alias that is not being expanded already, so an alias
+    /// may wrap a command of the same name without recursing forever
+    pub(crate) fn run_alias(&mut self, argc: &[&str]) -> Option<i32> {
+        let body = self.aliases.get(argc[0])?;
+        if self.alias_stack.len() >= ALIAS_DEPTH_MAX
+            || self.alias_stack.iter().any(|name| &name[..] == argc[0])
+        {
+            return None;
+        }
+
+        let cmd = substitute_positionals(body, argc);
+        self.alias_stack.push(Line::from(argc[0]));
+        let status = self.run_line(&cmd);
+        self.alias_stack.pop();
+
+        Some(status)
+    }
+
+    pub(crate) fn builtin_alias(&mut self, argc: &[&str]) -> i32 {
+        /* list the aliases when no definition is given */
+        if argc.len() == 1 {
+            let mut io = self.streams.io(&mut self.term);
+            for (name, value) in self.aliases.iter() {
+                writeln!(io, "{}='{}'", name, value);
+            }
+            return 0;
+        }
+
+        let mut status = 0;
+        for arg in &argc[1..] {
+            match arg.split_once('=') {
+                Some((name, value)) => {
+                    if is_valid_alias_name(name) {
+                        self.aliases.set(name, value);
+                    } else {
+                        self.print_error("alias: invalid alias name.");
+                        status = 1;
+                    }
+                }
+                None => match self.aliases.get(arg) {
+                    Some(value) => writeln!(self.streams.io(&mut self.term), "{}='{}'", arg, value),
+                    None => {
+                        self.print_error("alias: not found.");
+                        status = 1;
+                    }
+                },
+            }
+        }
+        status
+    }
+
+    pub(crate) fn builtin_unalias(&mut self, argc: &[&str]) -> i32 {
+        if argc.len() == 1 {
+            self.print_error("usage: unalias <name>...");
+            return 2;
+        }
+
+        let mut status = 0;
+        for name in &argc[1..] {
+            if !self.aliases.unset(name) {
+                self.print_error("unalias: not found.");
+                status = 1;
+            }
+        }
+        status
+    }
+}
@@ -0,0 +1,122 @@
+use super::parser::is_valid_name;
+use super::shell::Shell;
+use super::term::Terminal;
+use super::Line;
+
+/// Names of the commands built into the shell
+pub(crate) const BUILTINS: &[&str] = &[
+    "alias",
+    #[cfg(feature = "std")]
+    "bg",
+    "exit",
+    #[cfg(feature = "std")]
+    "fg",
+    "help",
+    "history",
+    #[cfg(feature = "std")]
+    "jobs",
+    "set",
+    #[cfg(feature = "std")]
+    "source",
+    "unalias",
+    "unset",
+];
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Runs `argc` if it names a command built into the shell, `None` leaves it to
+    /// the registered commands
+    pub(crate) fn run_builtin(&mut self, argc: &[&str]) -> Option<i32> {
+        match argc[0] {
+            "alias" => Some(self.builtin_alias(argc)),
+            "exit" => Some(self.builtin_exit(argc)),
+            "help" => Some(self.builtin_help()),
+            "history" => Some(self.builtin_history(argc)),
+            "set" => Some(self.builtin_set(argc)),
+            "unalias" => Some(self.builtin_unalias(argc)),
+            "unset" => Some(self.builtin_unset(argc)),
+            #[cfg(feature = "std")]
+            "source" => Some(self.builtin_source(argc)),
+            #[cfg(feature = "std")]
+            "jobs" => Some(self.builtin_jobs()),
+            #[cfg(feature = "std")]
+            "fg" => Some(self.builtin_fg(argc)),
+            #[cfg(feature = "std")]
+            "bg" => Some(self.builtin_bg(argc)),
+            _ => None,
+        }
+    }
+
+    fn builtin_set(&mut self, argc: &[&str]) -> i32 {
+        /* list the variables when no name is given */
+        if argc.len() == 1 {
+            let mut io = self.streams.io(&mut self.term);
+            for (name, value) in self.vars.iter() {
+                writeln!(io, "{}={}", name, value);
+            }
+            return 0;
+        }
+
+        /* the value is made of the remaining arguments */
+        let mut value = Line::new();
+        for (i, arg) in argc[2..].iter().enumerate() {
+            if i > 0 {
+                value.push(' ');
+            }
+            value.push_str(arg);
+        }
+
+        if !is_valid_name(argc[1]) {
+            self.print_error("set: invalid variable name.");
+            return 1;
+        }
+
+        self.vars.set(argc[1], &value);
+        0
+    }
+
+    fn builtin_unset(&mut self, argc: &[&str]) -> i32 {
+        if argc.len() == 1 {
+            self.print_error("usage: unset <name>...");
+            return 2;
+        }
+
+        for name in &argc[1..] {
+            self.vars.unset(name);
+        }
+        0
+    }
+
+    fn builtin_exit(&mut self, argc: &[&str]) -> i32 {
+        let status = match argc {
+            [_] => self.last_status(),
+            [_, status] => match status.parse() {
+                Ok(status) => status,
+                Err(_) => {
+                    self.print_error("exit: numeric argument required.");
+                    return 2;
+                }
+            },
+            _ => {
+                self.print_error("usage: exit [status]");
+                return 2;
+            }
+        };
+
+        self.exit_status = Some(status);
+        status
+    }
+
+    fn builtin_help(&mut self) -> i32 {
+        let mut io = self.streams.io(&mut self.term);
+        for name in BUILTINS {
+            writeln!(io, "{}", name);
+        }
+        for (name, _) in self.cmds.iter() {
+            writeln!(io, "{}", name);
+        }
+        for (name, value) in self.aliases.iter() {
+            writeln!(io, "{}='{}'", name, value);
+        }
+        0
+    }
+}
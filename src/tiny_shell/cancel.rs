@@ -0,0 +1,48 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "alloc")]
+type Flag = alloc::sync::Arc<AtomicBool>;
+#[cfg(not(feature = "alloc"))]
+type Flag = &'static AtomicBool;
+
+/* without an allocator every token shares the one flag */
+#[cfg(not(feature = "alloc"))]
+static CANCEL_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Flag raised by Ctrl+C while a command runs.
+///
+/// Long running handlers poll it through `Io::is_cancelled` and return early. Clones
+/// share the flag, so another thread or an interrupt handler can raise it as well.
+#[derive(Clone)]
+pub struct CancelToken {
+    flag: Flag,
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken {
+            #[cfg(feature = "alloc")]
+            flag: Flag::default(),
+            #[cfg(not(feature = "alloc"))]
+            flag: &CANCEL_FLAG,
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.flag.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> CancelToken {
+        CancelToken::new()
+    }
+}
@@ -0,0 +1,80 @@
+use super::builtins::BUILTINS;
+use super::shell::Shell;
+use super::term::Terminal;
+use super::Line;
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Names accepted as the first word of a command line
+    pub fn command_names(&self) -> impl Iterator<Item = &str> {
+        BUILTINS
+            .iter()
+            .copied()
+            .chain(self.cmds.iter().map(|(name, _)| *name))
+            .chain(self.aliases.iter().map(|(name, _)| name))
+    }
+
+    /// Completes the command name in front of the cursor.
+    ///
+    /// A unique match is completed followed by a space, several matches are completed
+    /// up to their common prefix and listed if that adds nothing.
+    pub(crate) fn complete(&mut self) {
+        let mut head = Line::new();
+        for c in &self.editor.chars()[..self.editor.cursor_pos()] {
+            head.push(*c);
+        }
+
+        /* only the first word names a command */
+        let prefix = head.trim_start();
+        if prefix.contains(char::is_whitespace) {
+            return;
+        }
+
+        let mut common = Line::new();
+        let mut matches = 0;
+        for name in self.command_names().filter(|name| name.starts_with(prefix)) {
+            if matches == 0 {
+                common.push_str(name);
+            } else {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(common.len().min(name.len()), |((i, _), _)| i);
+                common.truncate(len);
+            }
+            matches += 1;
+        }
+
+        if matches == 0 {
+            return;
+        }
+
+        let mut completion = Line::new();
+        completion.push_str(&common[prefix.len()..]);
+        if matches == 1 {
+            completion.push(' ');
+        }
+
+        if completion.is_empty() {
+            self.list_completions(prefix);
+        } else {
+            for c in completion.chars() {
+                self.editor.insert_char(c);
+            }
+        }
+        self.refresh_line();
+    }
+
+    fn list_completions(&mut self, prefix: &str) {
+        self.new_line();
+
+        let mut line = Line::new();
+        for name in self.command_names().filter(|name| name.starts_with(prefix)) {
+            line.push_str(name);
+            line.push_str("  ");
+        }
+
+        self.print(line.trim_end());
+        self.print("\n");
+    }
+}
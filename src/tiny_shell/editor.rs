@@ -0,0 +1,155 @@
+use super::{Line, List, CMD_LEN_MAX};
+
+/// Input buffer of the line being typed, together with the cursor tracking
+pub struct LineEditor {
+    buf: List<char, CMD_LEN_MAX>,
+    cursor_pos: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> LineEditor {
+        LineEditor {
+            buf: List::new(),
+            cursor_pos: 0,
+        }
+    }
+
+    pub fn char_cnt(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn cursor_pos(&self) -> usize {
+        self.cursor_pos
+    }
+
+    pub fn chars(&self) -> &[char] {
+        &self.buf
+    }
+
+    pub fn is_full(&self) -> bool {
+        /* the heap backed buffer grows on demand, the fixed one has to leave room in the
+         * `Line` of `CMD_LEN_MAX` bytes for a character of up to four */
+        cfg!(not(feature = "alloc"))
+            && self.buf.iter().map(|c| c.len_utf8()).sum::<usize>() + 4 > CMD_LEN_MAX
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if self.is_full() {
+            return;
+        }
+
+        self.buf.insert(self.cursor_pos, c);
+        self.cursor_pos += 1;
+    }
+
+    pub fn remove_char(&mut self, remove_pos: usize, cursor_fixed: bool) {
+        self.buf.remove(remove_pos - 1);
+
+        /* cursor shift left by on only if the remove event is triggered by the backspace */
+        if !cursor_fixed {
+            self.cursor_pos -= 1;
+        }
+
+        if self.cursor_pos > self.buf.len() {
+            self.cursor_pos = self.buf.len();
+        }
+    }
+
+    pub fn cursor_shift_one_left(&mut self) -> bool {
+        if self.cursor_pos > 0 {
+            self.cursor_pos -= 1;
+            return true;
+        }
+        false
+    }
+
+    pub fn cursor_shift_one_right(&mut self) -> bool {
+        if self.cursor_pos < self.buf.len() {
+            self.cursor_pos += 1;
+            return true;
+        }
+        false
+    }
+
+    /* start of the line of a multi-line input holding position `pos` */
+    fn line_start(&self, pos: usize) -> usize {
+        self.buf[..pos]
+            .iter()
+            .rposition(|&c| c == '\n')
+            .map_or(0, |i| i + 1)
+    }
+
+    /* end of the line of a multi-line input holding position `pos` */
+    fn line_end(&self, pos: usize) -> usize {
+        self.buf[pos..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(self.buf.len(), |i| pos + i)
+    }
+
+    /// Moves the cursor to the line above in a multi-line input, keeping its column as
+    /// far as the line reaches, false on the first line
+    pub fn cursor_line_up(&mut self) -> bool {
+        let start = self.line_start(self.cursor_pos);
+        if start == 0 {
+            return false;
+        }
+
+        let above = self.line_start(start - 1);
+        self.cursor_pos = above + (self.cursor_pos - start).min(start - 1 - above);
+        true
+    }
+
+    /// Moves the cursor to the line below in a multi-line input, keeping its column as
+    /// far as the line reaches, false on the last line
+    pub fn cursor_line_down(&mut self) -> bool {
+        let end = self.line_end(self.cursor_pos);
+        if end == self.buf.len() {
+            return false;
+        }
+
+        let column = self.cursor_pos - self.line_start(self.cursor_pos);
+        let below = end + 1;
+        self.cursor_pos = below + column.min(self.line_end(below) - below);
+        true
+    }
+
+    pub fn cursor_home(&mut self) {
+        self.cursor_pos = 0;
+    }
+
+    pub fn cursor_end(&mut self) {
+        self.cursor_pos = self.buf.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.cursor_pos = 0;
+    }
+
+    /// Overwrites the buffer with `s` and places the cursor at its end
+    pub fn set_text(&mut self, s: &str) {
+        self.buf.clear();
+        for c in s.chars() {
+            if self.is_full() {
+                break;
+            }
+            self.buf.push(c);
+        }
+        self.cursor_pos = self.buf.len();
+    }
+
+    pub fn text(&self) -> Line {
+        let mut line = Line::new();
+        for c in self.buf.iter() {
+            line.push(*c);
+        }
+        line
+    }
+}
+
+impl Default for LineEditor {
+    fn default() -> LineEditor {
+        LineEditor::new()
+    }
+}
@@ -0,0 +1,140 @@
+use super::jobs::{self, Wait};
+use super::shell::Shell;
+use super::term::Terminal;
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Exit status reported for a program found on `PATH` that could not be started
+pub const STATUS_CANNOT_EXECUTE: i32 = 126;
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Resolves a command name to an executable, names holding a `/` are taken as paths
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        let path = PathBuf::from(name);
+        return is_executable(&path).then_some(path);
+    }
+
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Lets command names that are neither aliases, builtins nor registered commands run
+    /// the program of that name found on `PATH`, off by default
+    pub fn enable_external_commands(&mut self, enable: bool) {
+        self.external = enable;
+    }
+
+    /// Runs `argc[0]` as a program from `PATH`, `None` if there is no such program.
+    ///
+    /// The child leads a process group of its own which owns the terminal while it runs,
+    /// so Ctrl+Z suspends it into a job. Piped input and output go through the shell's
+    /// streams, such a child cannot be suspended. A child killed by a signal reports 128
+    /// plus the signal number.
+    pub(crate) fn run_external(&mut self, argc: &[&str]) -> Option<i32> {
+        if !self.external {
+            return None;
+        }
+        let path = find_in_path(argc[0])?;
+        let piped = self.streams.stdin.is_some() || self.streams.stdout.is_some();
+        let owns_terminal = jobs::owns_terminal();
+
+        let mut cmd = Command::new(path);
+        cmd.args(&argc[1..]);
+        cmd.stdin(match self.streams.stdin {
+            Some(_) => Stdio::piped(),
+            None => Stdio::inherit(),
+        });
+        cmd.stdout(match self.streams.stdout {
+            Some(_) => Stdio::piped(),
+            None => Stdio::inherit(),
+        });
+        unsafe {
+            cmd.pre_exec(move || {
+                /* take the terminal from the child, before it gets the chance to read it */
+                libc::setpgid(0, 0);
+                if owns_terminal {
+                    libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                    libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                    libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+                }
+                Ok(())
+            });
+        }
+
+        self.term.suspend();
+        let result = cmd.spawn().map(|mut child| {
+            let pid = child.id() as libc::pid_t;
+            let input = self.streams.stdin.as_deref().unwrap_or("");
+
+            /* serve the pipes from other threads so a child filling one of them cannot
+             * dead lock against the shell */
+            std::thread::scope(|scope| {
+                if let Some(mut stdin) = child.stdin.take() {
+                    scope.spawn(move || stdin.write_all(input.as_bytes()));
+                }
+                let reader = child.stdout.take().map(|mut stdout| {
+                    scope.spawn(move || {
+                        let mut output = Vec::new();
+                        let _ = stdout.read_to_end(&mut output);
+                        output
+                    })
+                });
+
+                let wait = jobs::wait_foreground(pid, !piped);
+                let output = reader.and_then(|reader| reader.join().ok());
+                (pid, wait, output.unwrap_or_default())
+            })
+        });
+        if owns_terminal {
+            jobs::take_terminal();
+        }
+        self.term.resume();
+
+        match result {
+            Ok((pid, wait, output)) => {
+                if let Some(stdout) = &mut self.streams.stdout {
+                    stdout.push_str(&String::from_utf8_lossy(&output));
+                }
+                match wait {
+                    Wait::Exited(status) => Some(status),
+                    Wait::Stopped => Some(self.stop_job(pid, &argc.join(" "))),
+                }
+            }
+            Err(err) => {
+                self.print_error(&format!("{}: {}.", argc[0], err));
+                Some(STATUS_CANNOT_EXECUTE)
+            }
+        }
+    }
+
+    /// Starts `argc[0]` from `PATH` in the background, `None` if there is no such program
+    pub(crate) fn spawn_external(&mut self, argc: &[&str]) -> Option<Result<libc::pid_t, i32>> {
+        if !self.external {
+            return None;
+        }
+        let path = find_in_path(argc[0])?;
+
+        let mut cmd = Command::new(path);
+        cmd.args(&argc[1..]).stdin(Stdio::null()).process_group(0);
+
+        match cmd.spawn() {
+            Ok(child) => Some(Ok(child.id() as libc::pid_t)),
+            Err(err) => {
+                self.print_error(&format!("{}: {}.", argc[0], err));
+                Some(Err(STATUS_CANNOT_EXECUTE))
+            }
+        }
+    }
+}
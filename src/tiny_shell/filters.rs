@@ -0,0 +1,215 @@
+//! Text filters working on the piped input, e.g. `help | grep set | sort -r`.
+
+use super::shell::{CommandFn, Shell};
+use super::stream::Io;
+use super::term::Terminal;
+use super::{List, PIPE_LINES_MAX};
+
+/* number of lines `head` and `tail` print by default */
+const LINES_DEFAULT: usize = 10;
+
+/// Filters registered by `Shell::add_filters`
+pub const FILTERS: &[(&str, CommandFn)] = &[
+    ("grep", grep),
+    ("head", head),
+    ("sort", sort),
+    ("tail", tail),
+    ("wc", wc),
+];
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Registers `grep`, `head`, `sort`, `tail` and `wc` as commands
+    pub fn add_filters(&mut self) {
+        for (name, func) in FILTERS {
+            self.add_command(name, *func);
+        }
+    }
+}
+
+/* case-insensitive for ascii letters when `ignore_case` is set */
+fn contains(line: &str, pattern: &str, ignore_case: bool) -> bool {
+    if !ignore_case {
+        return line.contains(pattern);
+    }
+
+    let (line, pattern) = (line.as_bytes(), pattern.as_bytes());
+    pattern.is_empty()
+        || line
+            .windows(pattern.len())
+            .any(|window| window.eq_ignore_ascii_case(pattern))
+}
+
+/// `grep [-v] [-i] [-c] <pattern>`, prints the lines containing the pattern
+pub fn grep(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    let mut invert = false;
+    let mut ignore_case = false;
+    let mut count_only = false;
+    let mut pattern = None;
+    let mut pattern_cnt = 0;
+
+    for arg in &argc[1..] {
+        match *arg {
+            "-v" => invert = true,
+            "-i" => ignore_case = true,
+            "-c" => count_only = true,
+            _ => {
+                pattern = Some(*arg);
+                pattern_cnt += 1;
+            }
+        }
+    }
+
+    let pattern = match pattern {
+        Some(pattern) if pattern_cnt == 1 => pattern,
+        _ => {
+            io.error("usage: grep [-v] [-i] [-c] <pattern>");
+            return 2;
+        }
+    };
+
+    let mut count = 0;
+    for line in io.lines() {
+        if contains(line, pattern, ignore_case) != invert {
+            count += 1;
+            if !count_only {
+                writeln!(io, "{}", line);
+            }
+        }
+    }
+
+    if count_only {
+        writeln!(io, "{}", count);
+    }
+
+    /* like grep, fail when nothing matched */
+    (count == 0) as i32
+}
+
+/* parses the `-n N` or `-N` line count of head and tail */
+fn line_count(argc: &[&str]) -> Option<usize> {
+    match argc[1..] {
+        [] => Some(LINES_DEFAULT),
+        ["-n", n] => n.parse().ok(),
+        [n] => n.strip_prefix('-')?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// `head [-n N]`, prints the first lines
+pub fn head(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    let n = match line_count(argc) {
+        Some(n) => n,
+        None => {
+            io.error("usage: head [-n N]");
+            return 2;
+        }
+    };
+
+    for line in io.lines().take(n) {
+        writeln!(io, "{}", line);
+    }
+    0
+}
+
+/// `tail [-n N]`, prints the last lines
+pub fn tail(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    let n = match line_count(argc) {
+        Some(n) => n,
+        None => {
+            io.error("usage: tail [-n N]");
+            return 2;
+        }
+    };
+
+    let total = io.lines().count();
+    for line in io.lines().skip(total.saturating_sub(n)) {
+        writeln!(io, "{}", line);
+    }
+    0
+}
+
+/// `wc [-l] [-w] [-c]`, counts the lines, words and bytes
+pub fn wc(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    let input = io.input().unwrap_or("");
+    let counts = [
+        ("-l", input.lines().count()),
+        ("-w", input.split_whitespace().count()),
+        ("-c", input.len()),
+    ];
+
+    if argc[1..]
+        .iter()
+        .any(|arg| !matches!(*arg, "-l" | "-w" | "-c"))
+    {
+        io.error("usage: wc [-l] [-w] [-c]");
+        return 2;
+    }
+
+    /* print every count unless some are selected */
+    let mut first = true;
+    for (flag, count) in counts {
+        if argc.len() == 1 || argc.contains(&flag) {
+            if !first {
+                io.write_str(" ");
+            }
+            write!(io, "{}", count);
+            first = false;
+        }
+    }
+    io.write_str("\n");
+    0
+}
+
+/* compares the leading numbers of two lines, lines without one sort first */
+fn cmp_numeric(a: &str, b: &str) -> core::cmp::Ordering {
+    let number = |s: &str| {
+        let s = s.trim_start();
+        let end = s
+            .char_indices()
+            .find(|(i, c)| !(c.is_ascii_digit() || (*i == 0 && *c == '-')))
+            .map_or(s.len(), |(i, _)| i);
+        s[..end].parse::<i64>().ok()
+    };
+    number(a).cmp(&number(b)).then_with(|| a.cmp(b))
+}
+
+/// `sort [-r] [-n] [-u]`, sorts the lines
+pub fn sort(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    let mut reverse = false;
+    let mut numeric = false;
+    let mut unique = false;
+
+    for arg in &argc[1..] {
+        match *arg {
+            "-r" => reverse = true,
+            "-n" => numeric = true,
+            "-u" => unique = true,
+            _ => {
+                io.error("usage: sort [-r] [-n] [-u]");
+                return 2;
+            }
+        }
+    }
+
+    let mut lines: List<&str, PIPE_LINES_MAX> = List::new();
+    for line in io.lines() {
+        lines.push(line);
+    }
+
+    if numeric {
+        lines.sort_unstable_by(|a, b| cmp_numeric(a, b));
+    } else {
+        lines.sort_unstable();
+    }
+    if reverse {
+        lines.reverse();
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        if unique && i > 0 && lines[i - 1] == *line {
+            continue;
+        }
+        writeln!(io, "{}", line);
+    }
+    0
+}
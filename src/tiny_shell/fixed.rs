@@ -0,0 +1,284 @@
+//! Fixed-capacity containers backing the shell when the `alloc` feature is disabled.
+
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::str;
+
+/// Vector with an inline storage of `N` elements, pushes beyond the capacity are dropped
+#[derive(Clone, Copy)]
+pub struct FixedVec<T: Copy, const N: usize> {
+    data: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> FixedVec<T, N> {
+    pub const fn new() -> FixedVec<T, N> {
+        FixedVec {
+            data: [MaybeUninit::uninit(); N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.len < N {
+            self.data[self.len] = MaybeUninit::new(value);
+            self.len += 1;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        Some(unsafe { self.data[self.len].assume_init() })
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len, "insertion index out of bounds");
+        if self.len == N {
+            return;
+        }
+
+        /* shift the elements after the index one slot right */
+        for i in (index..self.len).rev() {
+            self.data[i + 1] = self.data[i];
+        }
+
+        self.data[index] = MaybeUninit::new(value);
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "removal index out of bounds");
+        let value = unsafe { self.data[index].assume_init() };
+
+        /* shift the elements after the index one slot left */
+        for i in index..(self.len - 1) {
+            self.data[i] = self.data[i + 1];
+        }
+
+        self.len -= 1;
+        value
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T: Copy, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> FixedVec<T, N> {
+        FixedVec::new()
+    }
+}
+
+impl<T: Copy, const N: usize> Deref for FixedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        /* the first `len` elements are always initialized */
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr() as *const T, self.len) }
+    }
+}
+
+impl<T: Copy, const N: usize> DerefMut for FixedVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+/// UTF-8 string with an inline storage of `N` bytes, characters beyond the capacity are dropped
+#[derive(Clone, Copy)]
+pub struct FixedString<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedString<N> {
+    pub const fn new() -> FixedString<N> {
+        FixedString {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        let mut utf8 = [0; 4];
+        let bytes = c.encode_utf8(&mut utf8).as_bytes();
+
+        if self.len + bytes.len() <= N {
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.push(c);
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            assert!(
+                self.as_str().is_char_boundary(len),
+                "truncation not on a char boundary"
+            );
+            self.len = len;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn as_str(&self) -> &str {
+        /* only whole characters are ever pushed into the buffer */
+        unsafe { str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> FixedString<N> {
+        FixedString::new()
+    }
+}
+
+impl<const N: usize> From<&str> for FixedString<N> {
+    fn from(s: &str) -> FixedString<N> {
+        let mut string = FixedString::new();
+        string.push_str(s);
+        string
+    }
+}
+
+impl<const N: usize> Deref for FixedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq for FixedString<N> {
+    fn eq(&self, other: &FixedString<N>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Write for FixedString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FixedString, FixedVec};
+
+    #[test]
+    fn vec_drops_pushes_beyond_the_capacity() {
+        let mut vec = FixedVec::<u8, 3>::new();
+        for i in 0..5 {
+            vec.push(i);
+        }
+        assert_eq!(&vec[..], [0, 1, 2]);
+
+        assert_eq!(vec.pop(), Some(2));
+        assert_eq!(vec.pop(), Some(1));
+        assert_eq!(vec.pop(), Some(0));
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn vec_insert_and_remove_shift_the_rest() {
+        let mut vec = FixedVec::<u8, 4>::new();
+        vec.push(1);
+        vec.push(3);
+        vec.insert(1, 2);
+        vec.insert(0, 0);
+        assert_eq!(&vec[..], [0, 1, 2, 3]);
+
+        /* a full vector drops the inserted element */
+        vec.insert(2, 9);
+        assert_eq!(&vec[..], [0, 1, 2, 3]);
+
+        assert_eq!(vec.remove(1), 1);
+        assert_eq!(vec.remove(2), 3);
+        assert_eq!(&vec[..], [0, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index out of bounds")]
+    fn vec_insert_past_the_end_panics() {
+        let mut vec = FixedVec::<u8, 4>::new();
+        vec.insert(1, 0);
+    }
+
+    #[test]
+    fn vec_truncate_and_clear() {
+        let mut vec = FixedVec::<u8, 4>::new();
+        vec.push(1);
+        vec.push(2);
+        vec.push(3);
+
+        vec.truncate(5);
+        assert_eq!(vec.len(), 3);
+        vec.truncate(1);
+        assert_eq!(&vec[..], [1]);
+        vec.clear();
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn string_drops_characters_that_do_not_fit() {
+        /* a two byte character does not fit in the last byte */
+        let mut s = FixedString::<4>::from("abc");
+        s.push('é');
+        assert_eq!(s.as_str(), "abc");
+        s.push('d');
+        assert_eq!(s.as_str(), "abcd");
+
+        assert_eq!(FixedString::<5>::from("añbñ").as_str(), "añb");
+    }
+
+    #[test]
+    fn string_truncates_on_a_char_boundary() {
+        let mut s = FixedString::<8>::from("añb");
+        s.truncate(10);
+        assert_eq!(s.as_str(), "añb");
+        s.truncate(3);
+        assert_eq!(s.as_str(), "añ");
+        s.clear();
+        assert_eq!(s.as_str(), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "truncation not on a char boundary")]
+    fn string_truncate_inside_a_char_panics() {
+        let mut s = FixedString::<8>::from("añb");
+        s.truncate(2);
+    }
+}
@@ -0,0 +1,244 @@
+use super::builtins::BUILTINS;
+use super::shell::{Shell, CONTINUATION_PROMPT};
+use super::term::Terminal;
+
+/// Part a piece of the input line plays, chosen by the highlighter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    /// Name of a builtin, registered command, alias or program
+    Command,
+    /// First word of a command naming nothing known
+    UnknownCommand,
+    Quoted,
+    Variable,
+    /// `;`, `&&`, `||`, `|`, `&` and the redirections
+    Operator,
+    Comment,
+    /// A quote or `${` that is never closed
+    Error,
+}
+
+impl Style {
+    /* select graphic rendition parameters of the color, `None` keeps the default */
+    fn sgr(self) -> Option<&'static str> {
+        match self {
+            Style::Plain => None,
+            Style::Command => Some("32"),
+            Style::UnknownCommand => Some("31"),
+            Style::Quoted => Some("33"),
+            Style::Variable => Some("36"),
+            Style::Operator => Some("35"),
+            Style::Comment => Some("90"),
+            Style::Error => Some("37;41"),
+        }
+    }
+}
+
+/// Highlighter hook, hands the pieces of `line` in order to `out`, `known` tells whether
+/// a word names a command
+pub type HighlightFn =
+    fn(line: &str, known: &dyn Fn(&str) -> bool, out: &mut dyn FnMut(&str, Style));
+
+fn is_operator(c: char) -> bool {
+    matches!(c, ';' | '|' | '&' | '<' | '>')
+}
+
+/* byte length of the quoted text at the start of `s`, `None` if it is never closed */
+fn quoted_len(s: &str) -> Option<usize> {
+    let quote = s.chars().next()?;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i + 1),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/* byte length of the word at the start of `s`, quotes may hide spaces and operators */
+fn word_len(s: &str) -> usize {
+    let mut i = 0;
+
+    while let Some(c) = s[i..].chars().next() {
+        match c {
+            c if c.is_whitespace() || is_operator(c) => return i,
+            '\'' | '"' => match quoted_len(&s[i..]) {
+                Some(n) => i += n,
+                None => return s.len(),
+            },
+            '\\' => {
+                i += 1;
+                i += s[i..].chars().next().map_or(0, char::len_utf8);
+            }
+            c => i += c.len_utf8(),
+        }
+    }
+    s.len()
+}
+
+/* byte length of the variable reference at the start of `s`, zero for a lone `$` */
+fn variable_len(s: &str) -> Option<usize> {
+    let rest = &s[1..];
+    match rest.chars().next() {
+        Some('{') => rest.find('}').map(|n| n + 2),
+        Some('?' | '@' | '#' | '0'..='9') => Some(2),
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => Some(
+            1 + rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len()),
+        ),
+        _ => Some(0),
+    }
+}
+
+fn highlight_word(word: &str, style: Style, out: &mut dyn FnMut(&str, Style)) {
+    let mut run = 0; //start of the text in the style of the word
+    let mut i = 0;
+
+    while let Some(c) = word[i..].chars().next() {
+        let piece = match c {
+            '\'' | '"' => Some(
+                quoted_len(&word[i..])
+                    .map_or((word.len() - i, Style::Error), |n| (n, Style::Quoted)),
+            ),
+            '$' => match variable_len(&word[i..]) {
+                Some(0) => None,
+                Some(n) => Some((n, Style::Variable)),
+                None => Some((word.len() - i, Style::Error)),
+            },
+            _ => None,
+        };
+
+        match piece {
+            Some((n, piece_style)) => {
+                if run < i {
+                    out(&word[run..i], style);
+                }
+                out(&word[i..i + n], piece_style);
+                i += n;
+                run = i;
+            }
+            None => {
+                /* a backslash keeps the next character in the word */
+                i += c.len_utf8();
+                if c == '\\' {
+                    i += word[i..].chars().next().map_or(0, char::len_utf8);
+                }
+            }
+        }
+    }
+
+    if run < word.len() {
+        out(&word[run..], style);
+    }
+}
+
+/// Default highlighter: command names in green or, if unknown, in red, quoted text,
+/// variables, operators and comments in colors of their own and an unclosed quote
+/// on a red background
+pub fn highlight(line: &str, known: &dyn Fn(&str) -> bool, out: &mut dyn FnMut(&str, Style)) {
+    let mut command_pos = true;
+    let mut rest = line;
+
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            c if c.is_whitespace() => {
+                let n = rest
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(rest.len());
+                out(&rest[..n], Style::Plain);
+                n
+            }
+            c if is_operator(c) => {
+                let n = match rest.get(..2) {
+                    Some("&&" | "||" | ">>") => 2,
+                    _ => 1,
+                };
+                out(&rest[..n], Style::Operator);
+
+                /* a command follows a separator, a file name a redirection */
+                command_pos = !matches!(c, '<' | '>');
+                n
+            }
+            '#' => {
+                out(rest, Style::Comment);
+                rest.len()
+            }
+            _ => {
+                let n = word_len(rest);
+                let word = &rest[..n];
+                let style = match command_pos {
+                    true if known(word) => Style::Command,
+                    true => Style::UnknownCommand,
+                    false => Style::Plain,
+                };
+                highlight_word(word, style, out);
+                command_pos = false;
+                n
+            }
+        };
+        rest = &rest[len..];
+    }
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Colors the input line through `highlighter`, e.g. the default `highlight`, or
+    /// prints it plain with `None`
+    pub fn set_highlighter(&mut self, highlighter: Option<HighlightFn>) {
+        self.highlighter = highlighter;
+    }
+
+    /// Prints the input line, colored if a highlighter is set, with the continuation
+    /// prompt in front of every further line of a multi-line input
+    pub(crate) fn print_input(&mut self) {
+        let (cmds, aliases) = (&self.cmds, &self.aliases);
+        #[cfg(feature = "std")]
+        let (external, path_cache) = (self.external, &self.path_cache);
+        let known = |word: &str| {
+            if BUILTINS.contains(&word)
+                || cmds.iter().any(|(name, _)| *name == word)
+                || aliases.get(word).is_some()
+            {
+                return true;
+            }
+
+            /* searching `PATH` is slow, look a word naming nothing else up only once */
+            #[cfg(feature = "std")]
+            if external {
+                let mut cache = path_cache.borrow_mut();
+                if let Some(&(_, found)) = cache.iter().find(|(name, _)| name == word) {
+                    return found;
+                }
+                let found = super::find_in_path(word).is_some();
+                cache.push((word.into(), found));
+                return found;
+            }
+            false
+        };
+
+        let term = &mut self.term;
+        let mut out = |piece: &str, style: Style| {
+            for (i, part) in piece.split('\n').enumerate() {
+                if i > 0 {
+                    term.write(b"\n\r");
+                    term.write(CONTINUATION_PROMPT.as_bytes());
+                }
+                match style.sgr() {
+                    Some(_) if part.is_empty() => {}
+                    Some(sgr) => write!(term, "\x1b[{}m{}\x1b[0m", sgr, part),
+                    None => term.write(part.as_bytes()),
+                }
+            }
+        };
+
+        let text = self.editor.text();
+        match self.highlighter {
+            Some(highlighter) => highlighter(&text, &known, &mut out),
+            None => out(&text, Style::Plain),
+        }
+    }
+}
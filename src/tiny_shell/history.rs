@@ -0,0 +1,290 @@
+use super::shell::Shell;
+use super::term::Terminal;
+use super::{Line, List, HISTORY_MAX_NUM};
+
+/// Which command lines the history keeps
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HistoryOptions {
+    /// Skip a line equal to the latest entry
+    pub ignore_dups: bool,
+    /// Remove older entries equal to the line before storing it
+    pub erase_dups: bool,
+    /// Skip lines beginning with a space
+    pub ignore_space: bool,
+}
+
+/// Most recent command lines, the oldest entry is dropped once the list is full
+pub struct History {
+    /* every line with the time it was stored, in seconds since the epoch, zero if unknown */
+    entries: List<(Line, u64), HISTORY_MAX_NUM>,
+    options: HistoryOptions,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            entries: List::new(),
+            options: HistoryOptions::default(),
+        }
+    }
+
+    pub fn set_options(&mut self, options: HistoryOptions) {
+        self.options = options;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Stores `cmd` with the current time, unless the options skip it
+    pub fn push(&mut self, cmd: &str) {
+        #[cfg(feature = "std")]
+        let time = super::host::unix_time();
+        #[cfg(not(feature = "std"))]
+        let time = 0;
+
+        self.push_at(cmd, time);
+    }
+
+    /// Stores `cmd` as typed at `time`, unless the options skip it
+    pub fn push_at(&mut self, cmd: &str, time: u64) {
+        if self.options.ignore_space && cmd.starts_with(' ') {
+            return;
+        }
+        if self.options.ignore_dups && self.get(0) == Some(cmd) {
+            return;
+        }
+        if self.options.erase_dups {
+            while let Some(i) = self.entries.iter().position(|(line, _)| &line[..] == cmd) {
+                self.entries.remove(i);
+            }
+        }
+
+        if self.entries.len() >= HISTORY_MAX_NUM {
+            self.entries.remove(0);
+        }
+        self.entries.push((Line::from(cmd), time));
+    }
+
+    /// Returns the `n`-th latest entry, `get(0)` is the last command typed
+    pub fn get(&self, n: usize) -> Option<&str> {
+        if n >= self.entries.len() {
+            return None;
+        }
+        Some(&self.entries[self.entries.len() - 1 - n].0)
+    }
+
+    /// Iterates from the oldest to the latest entry
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.entries.iter().map(|(cmd, _)| &cmd[..])
+    }
+
+    /// Iterates from the oldest to the latest entry along with the time it was stored
+    pub fn iter_timed(&self) -> impl DoubleEndedIterator<Item = (&str, u64)> {
+        self.entries.iter().map(|(cmd, time)| (&cmd[..], *time))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /* stores the expansion of a line in place of the line itself if it is the latest
+     * entry, subject to the options like any other line */
+    pub(crate) fn replace_latest(&mut self, cmd: &str, expanded: &str) {
+        if self.get(0) != Some(cmd) {
+            return;
+        }
+        if let Some((_, time)) = self.entries.pop() {
+            self.push_at(expanded, time);
+        }
+    }
+
+    /* drops the latest entry if it is `cmd`, a line whose expansion failed is not kept */
+    pub(crate) fn pop_latest_if(&mut self, cmd: &str) {
+        if self.get(0) == Some(cmd) {
+            self.entries.pop();
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new()
+    }
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Chooses which typed lines are stored in the history
+    pub fn set_history_options(&mut self, options: HistoryOptions) {
+        self.history.set_options(options);
+    }
+
+    /// Lets `parse` replace `!!`, `!N`, `!-N`, `!prefix`, `!$` and `^old^new` by the
+    /// history entries they refer to, on by default
+    pub fn set_history_expansion(&mut self, enable: bool) {
+        self.history_expansion = enable;
+    }
+
+    /// Expands the history references of `line`, `None` if it has none, or fails with the
+    /// reference that refers to no entry and the reason
+    pub(crate) fn expand_history<'s>(
+        &self,
+        line: &'s str,
+    ) -> Result<Option<Line>, (&'s str, &'static str)> {
+        /* the line itself may already be the latest entry */
+        let skip = usize::from(self.history.get(0) == Some(line));
+
+        /* `^old^new^` repeats the previous command with `old` replaced by `new` */
+        if let Some(rest) = line.strip_prefix('^') {
+            let (old, new) = match rest.split_once('^') {
+                Some((old, new)) => (old, new.strip_suffix('^').unwrap_or(new)),
+                None => (rest, ""),
+            };
+            let cmd = self.history.get(skip).unwrap_or("");
+            let Some(i) = cmd.find(old).filter(|_| !old.is_empty()) else {
+                return Err((line, ": substitution failed."));
+            };
+
+            let mut out = Line::from(&cmd[..i]);
+            out.push_str(new);
+            out.push_str(&cmd[i + old.len()..]);
+            return Ok(Some(out));
+        }
+
+        let mut out = Line::new();
+        let mut expanded = false;
+        let mut quote = None;
+        let mut rest = line;
+
+        while let Some(c) = rest.chars().next() {
+            let mut len = c.len_utf8();
+            match c {
+                '\'' | '"' if quote.is_none() => quote = Some(c),
+                c if quote == Some(c) => quote = None,
+                '\\' if quote != Some('\'') => {
+                    len += rest[1..].chars().next().map_or(0, char::len_utf8);
+                }
+                /* nothing is expanded between single quotes */
+                '!' if quote != Some('\'') => {
+                    if let Some((event, n)) = self.history_event(&rest[1..], skip) {
+                        let Some(event) = event else {
+                            return Err((&rest[..n + 1], ": event not found."));
+                        };
+                        out.push_str(event);
+                        expanded = true;
+                        rest = &rest[n + 1..];
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+        }
+
+        Ok(Some(out).filter(|_| expanded))
+    }
+
+    /* the text the reference behind a `!` stands for and its length, `None` if the `!`
+    is taken literally */
+    fn history_event(&self, spec: &str, skip: usize) -> Option<(Option<&str>, usize)> {
+        let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+        match spec.chars().next()? {
+            '!' => Some((self.history.get(skip), 1)),
+            '$' => {
+                let cmd = self.history.get(skip);
+                Some((cmd.and_then(|cmd| cmd.split_whitespace().next_back()), 1))
+            }
+            '-' => {
+                let n = digits(&spec[1..]);
+                let back = spec[1..n + 1].parse::<usize>().ok()?;
+                let event = back
+                    .checked_sub(1)
+                    .and_then(|back| self.history.get(back + skip));
+                Some((event, n + 1))
+            }
+            '0'..='9' => {
+                let n = digits(spec);
+                let number = spec[..n].parse::<usize>().ok()?;
+                let event = number
+                    .checked_sub(1)
+                    .and_then(|i| self.history.iter().nth(i));
+                Some((event, n))
+            }
+            c if c.is_whitespace() || matches!(c, '=' | '(' | '"' | '\'') => None,
+            _ => {
+                let n = spec
+                    .find(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '<' | '>'))
+                    .unwrap_or(spec.len());
+                /* a `!` right before an operator names no event, as in bash */
+                let prefix = &spec[..n];
+                if prefix.is_empty() {
+                    return Some((None, 0));
+                }
+                let event = (skip..self.history.len())
+                    .filter_map(|i| self.history.get(i))
+                    .find(|cmd| cmd.starts_with(prefix));
+                Some((event, n))
+            }
+        }
+    }
+
+    /// `history` lists the entries numbered from the oldest, `history n` only the last
+    /// `n` of them and `history -c` clears the list
+    pub(crate) fn builtin_history(&mut self, argc: &[&str]) -> i32 {
+        let count = match argc {
+            [_] => self.history.len(),
+            [_, "-c"] => {
+                self.history.clear();
+                return 0;
+            }
+            [_, n] => match n.parse::<usize>() {
+                Ok(n) => n.min(self.history.len()),
+                Err(_) => {
+                    self.print_error("history: numeric argument required.");
+                    return 2;
+                }
+            },
+            _ => {
+                self.print_error("usage: history [-c | n]");
+                return 2;
+            }
+        };
+
+        let skip = self.history.len() - count;
+        let mut io = self.streams.io(&mut self.term);
+        for (i, (cmd, time)) in self.history.iter_timed().enumerate().skip(skip) {
+            write!(io, "{:5}  ", i + 1);
+            #[cfg(feature = "std")]
+            if time > 0 {
+                write!(io, "{}  ", format_time(time));
+            }
+            #[cfg(not(feature = "std"))]
+            let _ = time;
+            writeln!(io, "{}", cmd);
+        }
+        0
+    }
+}
+
+/* local date and time of `time` as `YYYY-MM-DD HH:MM:SS` */
+#[cfg(feature = "std")]
+fn format_time(time: u64) -> std::string::String {
+    let Some(tm) = super::host::local_time(time) else {
+        return std::string::String::new();
+    };
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
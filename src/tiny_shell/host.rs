@@ -0,0 +1,350 @@
+use super::keys::{naws, WAKE};
+use super::term::Terminal;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* settings of the tty before raw mode, global so the panic hook can restore them */
+static SAVED_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+static PANIC_HOOK: Once = Once::new();
+
+/* raised by SIGWINCH, read by the terminal which reports the new size as input */
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+static SIGWINCH_HANDLER: Once = Once::new();
+
+/* pipe another thread writes to so a blocking read returns, see `wake` */
+static WAKE_PIPE: OnceLock<[libc::c_int; 2]> = OnceLock::new();
+
+thread_local! {
+    /* set while a command handler runs, its panics are reported by the shell */
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn saved_termios() -> MutexGuard<'static, Option<libc::termios>> {
+    SAVED_TERMIOS.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Leaves raw mode if it is on, safe to call from anywhere
+pub fn restore_terminal() {
+    if let Some(termios) = saved_termios().take() {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &termios);
+        }
+    }
+}
+
+/* restores the terminal before a panic message is printed, unless a handler panicked */
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.get() {
+                restore_terminal();
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+extern "C" fn on_sigwinch(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::Relaxed);
+}
+
+fn install_sigwinch_handler() {
+    SIGWINCH_HANDLER.call_once(|| unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigwinch as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        /* no SA_RESTART, a blocking read returns so the line is redrawn right away */
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+    });
+}
+
+fn window_size() -> Option<libc::winsize> {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    match unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } {
+        0 if size.ws_col > 0 => Some(size),
+        _ => None,
+    }
+}
+
+/// Sets up `wake`, before the terminal starts waiting so it also waits on the pipe
+pub(crate) fn init_wake() {
+    WAKE_PIPE.get_or_init(|| {
+        let mut fds = [-1; 2];
+        unsafe {
+            if libc::pipe(fds.as_mut_ptr()) == 0 {
+                for fd in fds {
+                    libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK);
+                    libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                }
+            }
+        }
+        fds
+    });
+}
+
+/// Makes the terminal waiting for input return `keys::WAKE`, callable from any thread
+pub(crate) fn wake() {
+    /* a full pipe already wakes the reader */
+    if let Some(fds) = WAKE_PIPE.get() {
+        unsafe {
+            libc::write(fds[1], b"w".as_ptr() as *const libc::c_void, 1);
+        }
+    }
+}
+
+/// Seconds since the epoch, zero if the clock is set before it
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Local date and time of `time` in seconds since the epoch, `None` if unknown
+pub(crate) fn local_time(time: u64) -> Option<libc::tm> {
+    let time = libc::time_t::try_from(time).ok()?;
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        match libc::localtime_r(&time, &mut tm).is_null() {
+            true => None,
+            false => Some(tm),
+        }
+    }
+}
+
+/// Runs a command handler, a panic in it comes back as its message
+pub(crate) fn catch_panic<F: FnOnce() -> i32>(f: F) -> Result<i32, String> {
+    install_panic_hook();
+    let catching = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(catching);
+
+    result.map_err(|payload| {
+        if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            String::from("panicked")
+        }
+    })
+}
+
+/// Restores the terminal when dropped, returned by `Shell::start`
+#[must_use]
+pub struct TerminalGuard(());
+
+impl TerminalGuard {
+    pub(crate) fn new() -> TerminalGuard {
+        TerminalGuard(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        restore_terminal();
+    }
+}
+
+/// Terminal backend for hosted targets, reads stdin and writes stdout in raw mode
+pub struct StdTerminal {
+    suspended: bool,
+    /* input made up by the terminal itself, the size report after SIGWINCH */
+    pending: VecDeque<u8>,
+}
+
+impl StdTerminal {
+    pub fn new() -> StdTerminal {
+        StdTerminal {
+            suspended: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /* waits until stdin is readable or `wake` is called, false on timeout */
+    fn wait_input(&mut self, timeout_ms: libc::c_int) -> bool {
+        let wake_fd = WAKE_PIPE.get().map_or(-1, |fds| fds[0]);
+        let mut fds = [
+            libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: wake_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout_ms) };
+        if ready <= 0 {
+            return false;
+        }
+
+        if fds[1].revents & libc::POLLIN != 0 {
+            let mut buf = [0u8; 64];
+            while unsafe { libc::read(wake_fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) }
+                > 0
+            {}
+            self.pending.extend(WAKE);
+        }
+        true
+    }
+
+    /* queues the size report if the window was resized */
+    fn check_resize(&mut self) {
+        if RESIZED.swap(false, Ordering::Relaxed) {
+            if let Some(size) = window_size() {
+                self.pending.extend(naws(size.ws_col, size.ws_row));
+            }
+        }
+    }
+
+    /// True when stdin is attached to a terminal rather than a pipe or a file
+    pub fn is_tty() -> bool {
+        unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
+    }
+}
+
+impl Default for StdTerminal {
+    fn default() -> StdTerminal {
+        StdTerminal::new()
+    }
+}
+
+impl Terminal for StdTerminal {
+    fn read_byte(&mut self) -> Option<u8> {
+        let mut c = 0u8;
+
+        loop {
+            self.check_resize();
+            if let Some(c) = self.pending.pop_front() {
+                return Some(c);
+            }
+            if !self.wait_input(-1) || !self.pending.is_empty() {
+                continue;
+            }
+
+            /* bypass the buffered std::io::Stdin so no byte is held back from the shell */
+            let n = unsafe {
+                libc::read(
+                    libc::STDIN_FILENO,
+                    &mut c as *mut u8 as *mut libc::c_void,
+                    1,
+                )
+            };
+            match n {
+                1 => return Some(c),
+                n if n < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {
+                    continue
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn read_byte_timeout(&mut self, timeout_ms: u32) -> Option<u8> {
+        self.check_resize();
+        if let Some(c) = self.pending.pop_front() {
+            return Some(c);
+        }
+
+        if !self.wait_input(timeout_ms as libc::c_int) {
+            return None;
+        }
+
+        self.pending.pop_front().or_else(|| self.read_byte())
+    }
+
+    fn poll_byte(&mut self) -> Option<u8> {
+        /* only a tty in raw mode hands over Ctrl+C as a byte, piped input is left for the
+         * commands still to be read from it */
+        if saved_termios().is_none() {
+            return None;
+        }
+        self.read_byte_timeout(0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        let _ = io::stdout().write_all(bytes);
+    }
+
+    /// Sends errors to stderr unless the line editor owns the tty
+    fn write_error(&mut self, bytes: &[u8]) {
+        if saved_termios().is_some() {
+            return self.write(bytes);
+        }
+        self.flush();
+        let _ = io::stderr().write_all(bytes);
+    }
+
+    /// Ends lines with a plain `\n` outside raw mode, e.g. for a script piped into a file
+    fn newline(&mut self) -> &'static [u8] {
+        match saved_termios().is_some() {
+            true => b"\n\r",
+            false => b"\n",
+        }
+    }
+
+    fn flush(&mut self) {
+        let _ = io::stdout().flush();
+    }
+
+    fn columns(&mut self) -> Option<usize> {
+        window_size().map(|size| size.ws_col as usize)
+    }
+
+    fn rows(&mut self) -> Option<usize> {
+        window_size().map(|size| size.ws_row as usize)
+    }
+
+    /// Switches the controlling tty into raw mode, keeping the old settings for restoring
+    fn enable_raw_mode(&mut self) {
+        let mut saved = saved_termios();
+        if saved.is_some() {
+            return;
+        }
+        install_panic_hook();
+        install_sigwinch_handler();
+
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return; //not a tty
+            }
+
+            let mut raw = termios;
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw);
+            *saved = Some(termios);
+        }
+    }
+
+    fn disable_raw_mode(&mut self) {
+        restore_terminal();
+    }
+
+    fn suspend(&mut self) {
+        self.flush();
+        if saved_termios().is_some() {
+            self.disable_raw_mode();
+            self.suspended = true;
+        }
+    }
+
+    fn resume(&mut self) {
+        if self.suspended {
+            self.enable_raw_mode();
+            self.suspended = false;
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use super::cancel::CancelToken;
+use super::host::catch_panic;
+use super::parser::{self, Args};
+use super::shell::Shell;
+use super::stream::Streams;
+use super::term::Terminal;
+use super::TYPEAHEAD_MAX;
+use std::string::String;
+use std::thread::{self, JoinHandle};
+use std::vec::Vec;
+
+/// Exit status of a foreground job suspended by Ctrl+Z, 128 plus SIGTSTP
+pub const STATUS_STOPPED: i32 = 128 + libc::SIGTSTP;
+
+/* how often `fg` reads the terminal while waiting for a command running on a thread */
+const FG_POLL_MS: u32 = 20;
+
+/* Ctrl+C and Ctrl+Z as read from the terminal in raw mode */
+const CTRL_C: u8 = 0x03;
+const CTRL_Z: u8 = 0x1a;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JobState {
+    Running,
+    Stopped,
+    Done(i32),
+}
+
+pub(crate) enum Task {
+    /// External program, the leader of its own process group
+    Process(libc::pid_t),
+    /// Registered command running on a thread, yields its status and output, the token
+    /// is the one its `Io` reports as cancelled
+    Thread(Option<JoinHandle<(i32, Vec<u8>)>>, CancelToken),
+}
+
+pub(crate) struct Job {
+    id: usize,
+    cmd: String,
+    task: Task,
+    state: JobState,
+    output: Vec<u8>,
+}
+
+/// How a foreground process ended up
+pub(crate) enum Wait {
+    Exited(i32),
+    Stopped,
+}
+
+/// Terminal collecting the output of a command running on a thread
+#[derive(Default)]
+struct Capture(Vec<u8>);
+
+impl Terminal for Capture {
+    fn read_byte(&mut self) -> Option<u8> {
+        None
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    /* the shell turns the line feeds into its own line ending when printing the output */
+    fn newline(&mut self) -> &'static [u8] {
+        b"\n"
+    }
+}
+
+/// Whether the shell owns the terminal and can hand it to the jobs it runs
+pub(crate) fn owns_terminal() -> bool {
+    unsafe {
+        libc::isatty(libc::STDIN_FILENO) == 1
+            && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+    }
+}
+
+/// Makes `pgid` the foreground process group of the terminal
+pub(crate) fn give_terminal(pgid: libc::pid_t) {
+    unsafe {
+        libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+    }
+}
+
+/// Makes the shell the foreground process group again
+pub(crate) fn take_terminal() {
+    unsafe {
+        /* the shell is in the background now, which would stop it on the terminal change */
+        let prev = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+        libc::signal(libc::SIGTTOU, prev);
+    }
+}
+
+/* exit status as reported by the shell, 128 plus the signal for killed processes */
+fn exit_status(status: libc::c_int) -> i32 {
+    if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        libc::WEXITSTATUS(status)
+    }
+}
+
+/// Waits for a foreground process to exit, or to stop if `allow_stop` is set.
+///
+/// A process that may not stop, e.g. one with piped input or output the shell has to
+/// keep serving, is continued right away.
+pub(crate) fn wait_foreground(pid: libc::pid_t, allow_stop: bool) -> Wait {
+    loop {
+        let mut status = 0;
+        let ret = unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) };
+
+        if ret < 0 {
+            if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Wait::Exited(1);
+        }
+
+        if libc::WIFSTOPPED(status) {
+            if allow_stop {
+                return Wait::Stopped;
+            }
+            unsafe {
+                libc::kill(-pid, libc::SIGCONT);
+            }
+            continue;
+        }
+
+        return Wait::Exited(exit_status(status));
+    }
+}
+
+impl Job {
+    fn state_name(&self) -> String {
+        match self.state {
+            JobState::Running => String::from("Running"),
+            JobState::Stopped => String::from("Stopped"),
+            JobState::Done(0) => String::from("Done"),
+            JobState::Done(status) => format!("Exit {}", status),
+        }
+    }
+
+    /* checks on the job without blocking */
+    fn update(&mut self) {
+        match &mut self.task {
+            Task::Process(pid) => {
+                let mut status = 0;
+                let flags = libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED;
+                let ret = unsafe { libc::waitpid(*pid, &mut status, flags) };
+
+                if ret < 0 {
+                    self.state = JobState::Done(1); //reaped elsewhere
+                } else if ret > 0 {
+                    self.state = if libc::WIFSTOPPED(status) {
+                        JobState::Stopped
+                    } else if libc::WIFCONTINUED(status) {
+                        JobState::Running
+                    } else {
+                        JobState::Done(exit_status(status))
+                    };
+                }
+            }
+            Task::Thread(handle, _) => {
+                if handle.as_ref().is_some_and(|handle| handle.is_finished()) {
+                    self.join();
+                }
+            }
+        }
+    }
+
+    /* blocks until the thread of the job returns */
+    fn join(&mut self) {
+        if let Task::Thread(handle, _) = &mut self.task {
+            if let Some(handle) = handle.take() {
+                let (status, output) = handle.join().unwrap_or((1, Vec::new()));
+                self.state = JobState::Done(status);
+                self.output = output;
+            }
+        }
+    }
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    fn add_job(&mut self, cmd: &str, task: Task, state: JobState) -> usize {
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        self.jobs.push(Job {
+            id,
+            cmd: String::from(cmd.trim()),
+            task,
+            state,
+            output: Vec::new(),
+        });
+        id
+    }
+
+    /* `+` marks the current job, `-` the previous one */
+    fn job_mark(&self, index: usize) -> char {
+        match self.jobs.len() - index {
+            1 => '+',
+            2 => '-',
+            _ => ' ',
+        }
+    }
+
+    fn print_job(&mut self, index: usize) {
+        let job = &self.jobs[index];
+        let line = format!(
+            "[{}]{} {:<8} {}\n",
+            job.id,
+            self.job_mark(index),
+            job.state_name(),
+            job.cmd
+        );
+        self.print(&line);
+    }
+
+    /* prints what a finished job wrote followed by its notification, then forgets it */
+    fn finish_job(&mut self, index: usize) {
+        let output = std::mem::take(&mut self.jobs[index].output);
+        self.print(&String::from_utf8_lossy(&output));
+        self.print_job(index);
+        self.jobs.remove(index);
+    }
+
+    /// Prints and forgets the background jobs that finished, called before the prompt
+    pub(crate) fn report_jobs(&mut self) {
+        for job in self.jobs.iter_mut() {
+            job.update();
+        }
+
+        let mut i = 0;
+        while i < self.jobs.len() {
+            if matches!(self.jobs[i].state, JobState::Done(_)) {
+                self.finish_job(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Records an external program suspended in the foreground and reports it
+    pub(crate) fn stop_job(&mut self, pid: libc::pid_t, cmd: &str) -> i32 {
+        self.add_job(cmd, Task::Process(pid), JobState::Stopped);
+        self.print("\n");
+        self.print_job(self.jobs.len() - 1);
+        STATUS_STOPPED
+    }
+
+    /// Starts `cmd` without waiting for it, the `cmd &` of a chain.
+    ///
+    /// A registered command runs on a thread with its output collected until it is
+    /// done, an external program runs in its own process group writing to the
+    /// terminal. Builtins, aliases and pipelines only run in the foreground.
+    pub(crate) fn run_background(&mut self, cmd: &str) -> i32 {
+        if parser::split_pipeline(cmd).count() > 1 {
+            self.print_error("pipelines cannot run in the background.");
+            return 1;
+        }
+
+        let args = match parser::parse_args(cmd, |name, out| self.expand_var(name, out)) {
+            Ok(args) => args,
+            Err(err) => {
+                self.print_error(err.message());
+                return 2;
+            }
+        };
+        let argc = args.to_list();
+
+        let name = match argc.first() {
+            Some(name) => *name,
+            None => return self.last_status(),
+        };
+
+        if args.redirects().next().is_some()
+            || self.aliases.get(name).is_some()
+            || super::builtins::BUILTINS.contains(&name)
+        {
+            self.print_error("only commands and programs can run in the background.");
+            return 1;
+        }
+
+        if let Some(&(_, func)) = self.cmds.iter().find(|(cmd_name, _)| *cmd_name == name) {
+            return self.spawn_thread_job(cmd, &args, func);
+        }
+
+        match self.spawn_external(&argc) {
+            Some(Ok(pid)) => {
+                let id = self.add_job(cmd, Task::Process(pid), JobState::Running);
+                self.print(&format!("[{}] {}\n", id, pid));
+                0
+            }
+            Some(Err(status)) => status,
+            None => {
+                self.print_error("unknown command.");
+                super::STATUS_UNKNOWN_CMD
+            }
+        }
+    }
+
+    fn spawn_thread_job(&mut self, cmd: &str, args: &Args, func: super::CommandFn) -> i32 {
+        let argc: Vec<String> = args.iter().map(String::from).collect();
+        let stdin = self.streams.stdin.clone();
+        let cancel = CancelToken::new();
+        let job_cancel = cancel.clone();
+
+        let handle = thread::spawn(move || {
+            let argc: Vec<&str> = argc.iter().map(String::as_str).collect();
+            let mut streams = Streams {
+                stdin,
+                cancel: job_cancel,
+                ..Default::default()
+            };
+            let mut out = Capture::default();
+            let mut io = streams.io(&mut out);
+            let status = match catch_panic(|| func(&argc, argc.len(), &mut io)) {
+                Ok(status) => status,
+                Err(msg) => {
+                    io.error(&format!("{}: panicked: {}.", argc[0], msg));
+                    1
+                }
+            };
+            (status, out.0)
+        });
+
+        let id = self.add_job(cmd, Task::Thread(Some(handle), cancel), JobState::Running);
+        self.print(&format!("[{}]\n", id));
+        0
+    }
+
+    /* index of the job named by `%N` or `N`, the current job if none is given */
+    fn find_job(&mut self, builtin: &str, argc: &[&str]) -> Option<usize> {
+        let found = match argc.get(1) {
+            None => self.jobs.len().checked_sub(1),
+            Some(arg) => arg
+                .trim_start_matches('%')
+                .parse::<usize>()
+                .ok()
+                .and_then(|id| self.jobs.iter().position(|job| job.id == id)),
+        };
+
+        if found.is_none() {
+            self.print_error(&format!("{}: no such job.", builtin));
+        }
+        found
+    }
+
+    pub(crate) fn builtin_jobs(&mut self) -> i32 {
+        for job in self.jobs.iter_mut() {
+            job.update();
+        }
+
+        let mut lines = String::new();
+        for (i, job) in self.jobs.iter().enumerate() {
+            lines += &format!(
+                "[{}]{} {:<8} {}\n",
+                job.id,
+                self.job_mark(i),
+                job.state_name(),
+                job.cmd
+            );
+        }
+        self.streams.io(&mut self.term).write_str(&lines);
+
+        /* finished jobs are listed once */
+        let mut i = 0;
+        while i < self.jobs.len() {
+            if matches!(self.jobs[i].state, JobState::Done(_)) {
+                let output = std::mem::take(&mut self.jobs[i].output);
+                self.print(&String::from_utf8_lossy(&output));
+                self.jobs.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        0
+    }
+
+    pub(crate) fn builtin_fg(&mut self, argc: &[&str]) -> i32 {
+        let index = match self.find_job("fg", argc) {
+            Some(index) => index,
+            None => return 1,
+        };
+
+        let cmd = self.jobs[index].cmd.clone();
+        self.print(&format!("{}\n", cmd));
+
+        match &self.jobs[index].task {
+            &Task::Process(pid) => {
+                self.term.suspend();
+                let owns_terminal = owns_terminal();
+                if owns_terminal {
+                    give_terminal(pid);
+                }
+                unsafe {
+                    libc::kill(-pid, libc::SIGCONT);
+                }
+                let wait = wait_foreground(pid, true);
+                if owns_terminal {
+                    take_terminal();
+                }
+                self.term.resume();
+
+                match wait {
+                    Wait::Exited(status) => {
+                        self.jobs.remove(index);
+                        status
+                    }
+                    Wait::Stopped => {
+                        self.jobs[index].state = JobState::Stopped;
+                        let job = self.jobs.remove(index);
+                        self.jobs.push(job);
+                        self.print("\n");
+                        self.print_job(self.jobs.len() - 1);
+                        STATUS_STOPPED
+                    }
+                }
+            }
+            Task::Thread(_, cancel) => {
+                let cancel = cancel.clone();
+
+                /* a thread cannot be suspended, Ctrl+Z leaves it running in the background */
+                loop {
+                    self.jobs[index].update();
+                    if let JobState::Done(status) = self.jobs[index].state {
+                        let output = std::mem::take(&mut self.jobs[index].output);
+                        self.print(&String::from_utf8_lossy(&output));
+                        self.jobs.remove(index);
+                        return status;
+                    }
+
+                    match self.term.read_byte_timeout(FG_POLL_MS) {
+                        Some(CTRL_Z) => {
+                            let job = self.jobs.remove(index);
+                            self.jobs.push(job);
+                            self.print_job(self.jobs.len() - 1);
+                            return STATUS_STOPPED;
+                        }
+                        Some(CTRL_C) => {
+                            /* like a tty, the interrupt discards what was typed before it */
+                            self.streams.typeahead.clear();
+                            self.streams.cancel.cancel();
+                            cancel.cancel();
+                        }
+                        Some(c) if self.streams.typeahead.len() < TYPEAHEAD_MAX => {
+                            self.streams.typeahead.push(c);
+                        }
+                        _ => {}
+                    }
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn builtin_bg(&mut self, argc: &[&str]) -> i32 {
+        let index = match self.find_job("bg", argc) {
+            Some(index) => index,
+            None => return 1,
+        };
+
+        let job = &mut self.jobs[index];
+        match (&job.task, job.state) {
+            (Task::Process(pid), JobState::Stopped) => {
+                unsafe {
+                    libc::kill(-*pid, libc::SIGCONT);
+                }
+                job.state = JobState::Running;
+                let line = format!("[{}] {} &\n", job.id, job.cmd);
+                self.print(&line);
+                0
+            }
+            _ => {
+                let msg = format!("bg: job {} already in background.", job.id);
+                self.print_error(&msg);
+                0
+            }
+        }
+    }
+}
@@ -0,0 +1,472 @@
+use core::ops::BitOr;
+
+#[allow(dead_code)]
+pub(crate) enum TermKeys {
+    NullCh = 0,      /* null character */
+    CtrlA = 1,       /* ctrl + a */
+    CtrlB = 2,       /* ctrl + b */
+    CtrlC = 3,       /* ctrl + c */
+    CtrlD = 4,       /* ctrl + d */
+    CtrlE = 5,       /* ctrl + e */
+    CtrlF = 6,       /* ctrl + f */
+    CtrlG = 7,       /* ctrl + g */
+    CtrlH = 8,       /* ctrl + h */
+    Tab = 9,         /* tab */
+    CtrlJ = 10,      /* ctrl + j */
+    CtrlK = 11,      /* ctrl + k */
+    CtrlL = 12,      /* ctrl + l */
+    Enter = 13,      /* enter */
+    CtrlN = 14,      /* ctrl + n */
+    CtrlO = 15,      /* ctrl + o */
+    CtrlP = 16,      /* ctrl + p */
+    CtrlQ = 17,      /* ctrl + r */
+    CtrlR = 18,      /* ctrl + r */
+    CtrlS = 19,      /* ctrl + s */
+    CtrlT = 20,      /* ctrl + t */
+    CtrlU = 21,      /* ctrl + u */
+    CtrlW = 23,      /* ctrl + w */
+    CtrlX = 24,      /* ctrl + x */
+    CtrlY = 25,      /* ctrl + y */
+    CtrlZ = 26,      /* ctrl + z */
+    EscSeq1 = 27,    /* first byte of the vt100/xterm escape sequence */
+    Ss3 = 79,        /* second byte of the ss3 escape sequence (ESC O) */
+    EscSeq2 = 91,    /* second byte of the csi escape sequence (ESC [) */
+    EscSeq4 = 126,   /* final byte of the vt100 escape sequence (ESC [ n ~) */
+    Backspace = 127, /* backspace */
+}
+
+/* telnet commands, a remote session reports its size as IAC SB NAWS w w h h IAC SE */
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const NAWS: u8 = 31;
+#[cfg(feature = "std")]
+const NOP: u8 = 241;
+
+/// Telnet no-op a terminal reads when woken up by another thread, decoded as an unknown key
+#[cfg(feature = "std")]
+pub(crate) const WAKE: [u8; 2] = [IAC, NOP];
+
+/// Modifier keys held while a key is pressed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const SHIFT: Modifiers = Modifiers(1);
+    pub const ALT: Modifiers = Modifiers(2);
+    pub const CTRL: Modifiers = Modifiers(4);
+
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn union(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+
+    /* xterm encodes the modifiers as `1 + bitmask` in the csi parameter */
+    const fn from_xterm_param(param: u16) -> Modifiers {
+        if param > 1 {
+            Modifiers((param - 1) as u8 & 0x07)
+        } else {
+            Modifiers::NONE
+        }
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        self.union(other)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+    /// The window changed to the given columns and rows, sent as a telnet NAWS sequence
+    Resize(u16, u16),
+    Unknown,
+}
+
+/// A decoded key press, independent of the terminal that produced it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub mods: Modifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode) -> Key {
+        Key {
+            code,
+            mods: Modifiers::NONE,
+        }
+    }
+
+    pub const fn with_mods(code: KeyCode, mods: Modifiers) -> Key {
+        Key { code, mods }
+    }
+
+    /// Ctrl + letter, e.g. `Key::ctrl('a')`
+    pub const fn ctrl(c: char) -> Key {
+        Key::with_mods(KeyCode::Char(c), Modifiers::CTRL)
+    }
+
+    /// Alt + character, sent by terminals as an escape followed by the character
+    pub const fn alt(c: char) -> Key {
+        Key::with_mods(KeyCode::Char(c), Modifiers::ALT)
+    }
+
+    /// Maps a single byte outside of any escape sequence
+    fn from_byte(c: u8) -> Key {
+        match c {
+            c if c == TermKeys::NullCh as u8 => Key::ctrl(' '),
+            c if c == TermKeys::Tab as u8 => Key::new(KeyCode::Tab),
+            c if c == TermKeys::Enter as u8 => Key::new(KeyCode::Enter),
+            c if c == TermKeys::EscSeq1 as u8 => Key::new(KeyCode::Escape),
+            c if c == TermKeys::Backspace as u8 => Key::new(KeyCode::Backspace),
+            1..=26 => Key::ctrl((b'a' + c - 1) as char),
+            28..=31 => Key::ctrl((b'\\' + c - 28) as char),
+            _ => Key::new(KeyCode::Char(c as char)),
+        }
+    }
+}
+
+const CSI_PARAMS_MAX: usize = 4;
+const CSI_LEN_MAX: usize = 16;
+const SUBNEG_LEN_MAX: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DecodeState {
+    Ground,
+    Escape,
+    Csi,
+    Ss3,
+    Utf8 { remain: u8 },
+    Iac,
+    IacOption,
+    Subneg { iac: bool },
+}
+
+/// State machine turning the raw input bytes into key presses.
+///
+/// Understands utf-8 text, control characters, `ESC x` (alt + x), csi sequences
+/// (`ESC [ params final`, with xterm modifier parameters), ss3 sequences (`ESC O x`) and
+/// the telnet window size report. Unrecognized sequences are consumed completely and
+/// reported as `KeyCode::Unknown`.
+pub struct KeyDecoder {
+    state: DecodeState,
+    params: [u16; CSI_PARAMS_MAX],
+    param_cnt: usize,
+    seq_len: usize,
+    utf8: u32,
+    subneg: [u8; SUBNEG_LEN_MAX],
+}
+
+impl KeyDecoder {
+    pub const fn new() -> KeyDecoder {
+        KeyDecoder {
+            state: DecodeState::Ground,
+            params: [0; CSI_PARAMS_MAX],
+            param_cnt: 0,
+            seq_len: 0,
+            utf8: 0,
+            subneg: [0; SUBNEG_LEN_MAX],
+        }
+    }
+
+    /// True while the decoder waits for the rest of a multi-byte sequence
+    pub fn is_pending(&self) -> bool {
+        self.state != DecodeState::Ground
+    }
+
+    /// Feeds one input byte, returns the key once a complete sequence has been read
+    pub fn feed(&mut self, c: u8) -> Option<Key> {
+        match self.state {
+            DecodeState::Ground => self.feed_ground(c),
+            DecodeState::Escape => self.feed_escape(c),
+            DecodeState::Csi => self.feed_csi(c),
+            DecodeState::Ss3 => self.feed_ss3(c),
+            DecodeState::Utf8 { remain } => self.feed_utf8(c, remain),
+            DecodeState::Iac => self.feed_iac(c),
+            DecodeState::IacOption => {
+                self.state = DecodeState::Ground;
+                Some(Key::new(KeyCode::Unknown))
+            }
+            DecodeState::Subneg { iac } => self.feed_subneg(c, iac),
+        }
+    }
+
+    /// Resolves an incomplete sequence after the input stayed silent, e.g. a bare Escape
+    pub fn timeout(&mut self) -> Option<Key> {
+        let state = self.state;
+        self.state = DecodeState::Ground;
+
+        match state {
+            DecodeState::Ground => None,
+            DecodeState::Escape => Some(Key::new(KeyCode::Escape)),
+            DecodeState::Csi if self.seq_len == 0 => Some(Key::alt('[')),
+            DecodeState::Ss3 => Some(Key::alt('O')),
+            _ => Some(Key::new(KeyCode::Unknown)),
+        }
+    }
+
+    fn feed_ground(&mut self, c: u8) -> Option<Key> {
+        match c {
+            c if c == TermKeys::EscSeq1 as u8 => {
+                self.state = DecodeState::Escape;
+                None
+            }
+            0xc2..=0xdf => self.start_utf8(c as u32 & 0x1f, 1),
+            0xe0..=0xef => self.start_utf8(c as u32 & 0x0f, 2),
+            0xf0..=0xf4 => self.start_utf8(c as u32 & 0x07, 3),
+            IAC => {
+                self.state = DecodeState::Iac;
+                None
+            }
+            0x80..=0xff => Some(Key::new(KeyCode::Unknown)),
+            _ => Some(Key::from_byte(c)),
+        }
+    }
+
+    fn start_utf8(&mut self, bits: u32, remain: u8) -> Option<Key> {
+        self.utf8 = bits;
+        self.state = DecodeState::Utf8 { remain };
+        None
+    }
+
+    fn feed_utf8(&mut self, c: u8, remain: u8) -> Option<Key> {
+        if c & 0xc0 != 0x80 {
+            /* broken sequence, drop it and decode the byte from scratch */
+            self.state = DecodeState::Ground;
+            return self.feed_ground(c);
+        }
+
+        self.utf8 = (self.utf8 << 6) | (c as u32 & 0x3f);
+        if remain > 1 {
+            self.state = DecodeState::Utf8 { remain: remain - 1 };
+            return None;
+        }
+
+        self.state = DecodeState::Ground;
+        match char::from_u32(self.utf8) {
+            Some(ch) => Some(Key::new(KeyCode::Char(ch))),
+            None => Some(Key::new(KeyCode::Unknown)),
+        }
+    }
+
+    fn feed_iac(&mut self, c: u8) -> Option<Key> {
+        match c {
+            SB => {
+                self.state = DecodeState::Subneg { iac: false };
+                self.seq_len = 0;
+                None
+            }
+            251..=254 => {
+                /* WILL, WONT, DO and DONT carry an option byte */
+                self.state = DecodeState::IacOption;
+                None
+            }
+            _ => {
+                self.state = DecodeState::Ground;
+                Some(Key::new(KeyCode::Unknown))
+            }
+        }
+    }
+
+    fn feed_subneg(&mut self, c: u8, iac: bool) -> Option<Key> {
+        match (iac, c) {
+            (false, IAC) => {
+                self.state = DecodeState::Subneg { iac: true };
+                return None;
+            }
+            (true, SE) => {
+                self.state = DecodeState::Ground;
+                return Some(Key::new(self.subneg_code()));
+            }
+            (true, c) if c != IAC => {
+                self.state = DecodeState::Ground;
+                return Some(Key::new(KeyCode::Unknown));
+            }
+            _ => {} //payload byte, IAC IAC is an escaped 255
+        }
+
+        if self.seq_len >= CSI_LEN_MAX {
+            self.state = DecodeState::Ground;
+            return Some(Key::new(KeyCode::Unknown));
+        }
+        if self.seq_len < SUBNEG_LEN_MAX {
+            self.subneg[self.seq_len] = c;
+        }
+        self.seq_len += 1;
+        self.state = DecodeState::Subneg { iac: false };
+        None
+    }
+
+    fn subneg_code(&self) -> KeyCode {
+        match self.subneg {
+            [NAWS, w1, w0, h1, h0] if self.seq_len == SUBNEG_LEN_MAX => {
+                KeyCode::Resize(u16::from_be_bytes([w1, w0]), u16::from_be_bytes([h1, h0]))
+            }
+            _ => KeyCode::Unknown,
+        }
+    }
+
+    fn feed_escape(&mut self, c: u8) -> Option<Key> {
+        match c {
+            c if c == TermKeys::EscSeq2 as u8 => {
+                self.state = DecodeState::Csi;
+                self.params = [0; CSI_PARAMS_MAX];
+                self.param_cnt = 0;
+                self.seq_len = 0;
+                None
+            }
+            c if c == TermKeys::Ss3 as u8 => {
+                self.state = DecodeState::Ss3;
+                None
+            }
+            c if c == TermKeys::EscSeq1 as u8 => {
+                /* escape pressed twice, report the first one and wait on the second */
+                Some(Key::new(KeyCode::Escape))
+            }
+            0x80..=0xff => {
+                self.state = DecodeState::Ground;
+                Some(Key::new(KeyCode::Unknown))
+            }
+            _ => {
+                self.state = DecodeState::Ground;
+                let key = Key::from_byte(c);
+                Some(Key::with_mods(key.code, key.mods | Modifiers::ALT))
+            }
+        }
+    }
+
+    fn feed_csi(&mut self, c: u8) -> Option<Key> {
+        self.seq_len += 1;
+
+        match c {
+            b'0'..=b'9' => {
+                if self.param_cnt == 0 {
+                    self.param_cnt = 1;
+                }
+                if self.param_cnt <= CSI_PARAMS_MAX {
+                    let param = &mut self.params[self.param_cnt - 1];
+                    *param = param.saturating_mul(10).saturating_add((c - b'0') as u16);
+                }
+            }
+            b';' => {
+                self.param_cnt = if self.param_cnt == 0 {
+                    2
+                } else {
+                    self.param_cnt + 1
+                };
+            }
+            0x20..=0x3f => {} //private markers and intermediate bytes
+            0x40..=0x7e => {
+                self.state = DecodeState::Ground;
+                return Some(self.csi_key(c));
+            }
+            _ => {
+                /* control byte inside the sequence, abandon it */
+                self.state = DecodeState::Ground;
+                return Some(Key::new(KeyCode::Unknown));
+            }
+        }
+
+        if self.seq_len >= CSI_LEN_MAX {
+            self.state = DecodeState::Ground;
+            return Some(Key::new(KeyCode::Unknown));
+        }
+        None
+    }
+
+    fn csi_key(&self, final_byte: u8) -> Key {
+        let mods = Modifiers::from_xterm_param(self.params[1]);
+
+        let code = match final_byte {
+            b'A' => KeyCode::Up,
+            b'B' => KeyCode::Down,
+            b'C' => KeyCode::Right,
+            b'D' => KeyCode::Left,
+            b'H' => KeyCode::Home,
+            b'F' => KeyCode::End,
+            b'P' => KeyCode::F(1),
+            b'Q' => KeyCode::F(2),
+            b'R' => KeyCode::F(3),
+            b'S' => KeyCode::F(4),
+            b'Z' => return Key::with_mods(KeyCode::Tab, mods | Modifiers::SHIFT),
+            c if c == TermKeys::EscSeq4 as u8 => match self.params[0] {
+                1 | 7 => KeyCode::Home,
+                2 => KeyCode::Insert,
+                3 => KeyCode::Delete,
+                4 | 8 => KeyCode::End,
+                5 => KeyCode::PageUp,
+                6 => KeyCode::PageDown,
+                n @ 11..=15 => KeyCode::F((n - 10) as u8),
+                n @ 17..=21 => KeyCode::F((n - 11) as u8),
+                n @ 23..=24 => KeyCode::F((n - 12) as u8),
+                _ => KeyCode::Unknown,
+            },
+            _ => KeyCode::Unknown,
+        };
+
+        Key::with_mods(code, mods)
+    }
+
+    fn feed_ss3(&mut self, c: u8) -> Option<Key> {
+        self.state = DecodeState::Ground;
+
+        let code = match c {
+            b'A' => KeyCode::Up,
+            b'B' => KeyCode::Down,
+            b'C' => KeyCode::Right,
+            b'D' => KeyCode::Left,
+            b'H' => KeyCode::Home,
+            b'F' => KeyCode::End,
+            b'M' => KeyCode::Enter,
+            b'P' => KeyCode::F(1),
+            b'Q' => KeyCode::F(2),
+            b'R' => KeyCode::F(3),
+            b'S' => KeyCode::F(4),
+            _ => KeyCode::Unknown,
+        };
+
+        Some(Key::new(code))
+    }
+}
+
+/// Bytes of the telnet NAWS sequence reporting a window of `cols` by `rows`
+#[cfg(feature = "alloc")]
+pub(crate) fn naws(cols: u16, rows: u16) -> impl Iterator<Item = u8> {
+    let size = [cols.to_be_bytes(), rows.to_be_bytes()];
+    let payload = size.into_iter().flatten().flat_map(|b| {
+        /* a 255 in the payload is doubled so it is not taken for IAC */
+        core::iter::repeat_n(b, if b == IAC { 2 } else { 1 })
+    });
+
+    [IAC, SB, NAWS].into_iter().chain(payload).chain([IAC, SE])
+}
+
+impl Default for KeyDecoder {
+    fn default() -> KeyDecoder {
+        KeyDecoder::new()
+    }
+}
@@ -0,0 +1,559 @@
+use super::{Line, List, ARGS_MAX, REDIRECTS_MAX};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    UnterminatedQuote,
+    BadSubstitution,
+    UnexpectedOperator(ChainOp),
+    UnexpectedPipe,
+    UnexpectedAmpersand,
+    UnexpectedEnd,
+    MissingRedirectTarget,
+}
+
+impl ParseError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ParseError::UnterminatedQuote => "unterminated quote.",
+            ParseError::BadSubstitution => "bad substitution.",
+            ParseError::UnexpectedOperator(ChainOp::Seq) => "syntax error near `;`.",
+            ParseError::UnexpectedOperator(ChainOp::And) => "syntax error near `&&`.",
+            ParseError::UnexpectedOperator(ChainOp::Or) => "syntax error near `||`.",
+            ParseError::UnexpectedPipe => "syntax error near `|`.",
+            ParseError::UnexpectedAmpersand => "syntax error near `&`.",
+            ParseError::UnexpectedEnd => "syntax error: unexpected end of line.",
+            ParseError::MissingRedirectTarget => "syntax error: missing file name to redirect to.",
+        }
+    }
+}
+
+/// Operator joining a command to the previous one on the same line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainOp {
+    /// `;`, always runs the command
+    Seq,
+    /// `&&`, runs the command if the previous one succeeded
+    And,
+    /// `||`, runs the command if the previous one failed
+    Or,
+}
+
+impl ChainOp {
+    /// Whether a command joined by this operator runs after a command exiting with `status`
+    pub fn should_run(self, status: i32) -> bool {
+        match self {
+            ChainOp::Seq => true,
+            ChainOp::And => status == 0,
+            ChainOp::Or => status != 0,
+        }
+    }
+}
+
+/// Iterator over the commands of a line joined by `;`, `&&`, `||` and `&`.
+///
+/// Yields every command together with the operator in front of it (`None` for the
+/// first one). A command ended by `&` keeps it, see `split_background`, and the next
+/// one follows it like after `;`. Operators inside quotes, escaped with a backslash or
+/// behind a `#` comment are part of the command text.
+pub struct Chain<'s> {
+    src: &'s str,
+    pos: usize,
+    prev_op: Option<ChainOp>,
+    done: bool,
+}
+
+/// Splits a command line into the commands joined by `;`, `&&`, `||` and `&`
+pub fn split_chain(line: &str) -> Chain<'_> {
+    Chain {
+        src: line,
+        pos: 0,
+        prev_op: None,
+        done: false,
+    }
+}
+
+/// Operator found between two commands
+#[derive(Clone, Copy)]
+enum Operator {
+    Chain(ChainOp),
+    Pipe,
+    Background,
+}
+
+/* finds the first operator from `pos` on and the position behind it, returns the end of
+ * the text if there is none */
+fn find_operator(src: &str, pos: usize) -> (usize, Option<(Operator, usize)>) {
+    let bytes = src.as_bytes();
+    let mut in_single_quote = false;
+    let mut in_double_quote = false;
+    let mut word_start = true;
+    let mut i = pos;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+
+        if in_single_quote {
+            in_single_quote = c != b'\'';
+        } else if in_double_quote {
+            match c {
+                b'\\' => i += 1,
+                b'"' => in_double_quote = false,
+                _ => {}
+            }
+        } else {
+            let op = match (c, next) {
+                (b'\\', _) => {
+                    i += 1;
+                    None
+                }
+                (b'\'', _) => {
+                    in_single_quote = true;
+                    None
+                }
+                (b'"', _) => {
+                    in_double_quote = true;
+                    None
+                }
+                (b'#', _) if word_start => return (i, None),
+                (b';', _) => Some((Operator::Chain(ChainOp::Seq), 1)),
+                (b'&', Some(b'&')) => Some((Operator::Chain(ChainOp::And), 2)),
+                (b'&', _) => Some((Operator::Background, 1)),
+                (b'|', Some(b'|')) => Some((Operator::Chain(ChainOp::Or), 2)),
+                (b'|', _) => Some((Operator::Pipe, 1)),
+                _ => None,
+            };
+            if let Some((op, len)) = op {
+                return (i, Some((op, i + len)));
+            }
+        }
+
+        word_start = !in_single_quote && !in_double_quote && c.is_ascii_whitespace();
+        i += 1;
+    }
+
+    (bytes.len().min(i), None)
+}
+
+impl<'s> Chain<'s> {
+    /* finds the end of the current command and the operator behind it, pipes are part
+     * of the command and so is a `&`, which is reported by the flag */
+    fn find_end(&self) -> (usize, Option<(ChainOp, usize)>, bool) {
+        let mut pos = self.pos;
+        loop {
+            match find_operator(self.src, pos) {
+                (_, Some((Operator::Pipe, next))) => pos = next,
+                (_, Some((Operator::Background, next))) => {
+                    return (next, Some((ChainOp::Seq, next)), true)
+                }
+                (end, Some((Operator::Chain(op), next))) => return (end, Some((op, next)), false),
+                (end, None) => return (end, None, false),
+            }
+        }
+    }
+}
+
+/// Splits the `&` off a command of a chain, the flag tells whether it was there
+pub fn split_background(cmd: &str) -> (&str, bool) {
+    let mut pos = 0;
+    loop {
+        match find_operator(cmd, pos) {
+            (_, Some((Operator::Pipe, next))) => pos = next,
+            (end, Some((Operator::Background, _))) => return (&cmd[..end], true),
+            _ => return (cmd, false),
+        }
+    }
+}
+
+impl<'s> Iterator for Chain<'s> {
+    type Item = Result<(Option<ChainOp>, &'s str), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let (end, op, background) = self.find_end();
+        let cmd = &self.src[self.pos..end];
+        let prev_op = self.prev_op;
+
+        match op {
+            Some((op, next_pos)) => {
+                self.pos = next_pos;
+                self.prev_op = Some(op);
+            }
+            None => self.done = true,
+        }
+
+        if background && split_background(cmd).0.trim().is_empty() {
+            self.done = true;
+            return Some(Err(ParseError::UnexpectedAmpersand));
+        }
+
+        if cmd.trim().is_empty() {
+            self.done = true;
+            return match (op, prev_op) {
+                (Some((op, _)), _) => Some(Err(ParseError::UnexpectedOperator(op))),
+                (None, Some(ChainOp::And | ChainOp::Or)) => Some(Err(ParseError::UnexpectedEnd)),
+                (None, _) => None,
+            };
+        }
+
+        Some(Ok((prev_op, cmd)))
+    }
+}
+
+/// Iterator over the stages of a pipeline `a | b | c`.
+///
+/// Takes a single command of a chain, a `;`, `&&`, `||` or `&` ends the pipeline.
+pub struct Pipeline<'s> {
+    src: &'s str,
+    pos: usize,
+    piped: bool,
+    done: bool,
+}
+
+/// Splits a command into the stages of its pipeline
+pub fn split_pipeline(cmd: &str) -> Pipeline<'_> {
+    Pipeline {
+        src: cmd,
+        pos: 0,
+        piped: false,
+        done: false,
+    }
+}
+
+impl<'s> Iterator for Pipeline<'s> {
+    type Item = Result<&'s str, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let (end, op) = find_operator(self.src, self.pos);
+        let stage = &self.src[self.pos..end];
+        let piped = self.piped;
+        let pipe_follows = matches!(op, Some((Operator::Pipe, _)));
+
+        match op {
+            Some((Operator::Pipe, next_pos)) => {
+                self.pos = next_pos;
+                self.piped = true;
+            }
+            _ => self.done = true,
+        }
+
+        if stage.trim().is_empty() {
+            self.done = true;
+            return match (pipe_follows, piped) {
+                (true, _) => Some(Err(ParseError::UnexpectedPipe)),
+                (false, true) => Some(Err(ParseError::UnexpectedEnd)),
+                (false, false) => None,
+            };
+        }
+
+        Some(Ok(stage))
+    }
+}
+
+/// Checks the operators of a whole line, so nothing runs of a line that does not parse
+pub fn check_syntax(line: &str) -> Result<(), ParseError> {
+    for cmd in split_chain(line) {
+        let (_, cmd) = cmd?;
+        for stage in split_pipeline(split_background(cmd).0) {
+            stage?;
+        }
+    }
+    Ok(())
+}
+
+/// Redirection of a command's input or output to a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Redirect {
+    /// `< file`, reads the input from the file
+    Input,
+    /// `> file`, replaces the file with the output
+    Output,
+    /// `>> file`, appends the output to the file
+    Append,
+}
+
+/// Arguments of a command line after expansion and quote removal, kept in one buffer
+pub struct Args {
+    buf: Line,
+    bounds: List<(usize, usize), ARGS_MAX>,
+    redirects: List<(Redirect, (usize, usize)), REDIRECTS_MAX>,
+}
+
+impl Args {
+    fn new() -> Args {
+        Args {
+            buf: Line::new(),
+            bounds: List::new(),
+            redirects: List::new(),
+        }
+    }
+
+    /* ends the word started at `start`, which names a file if a redirection is pending */
+    fn end_word(&mut self, start: usize, redirect: &mut Option<Redirect>) {
+        let bounds = (start, self.buf.len());
+        match redirect.take() {
+            Some(op) => self.redirects.push((op, bounds)),
+            None => self.bounds.push(bounds),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn get(&self, n: usize) -> Option<&str> {
+        self.bounds
+            .get(n)
+            .map(|(start, end)| &self.buf[*start..*end])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.bounds
+            .iter()
+            .map(|(start, end)| &self.buf[*start..*end])
+    }
+
+    /// Redirections in the order given, each with the file it names
+    pub fn redirects(&self) -> impl Iterator<Item = (Redirect, &str)> {
+        self.redirects
+            .iter()
+            .map(|(op, (start, end))| (*op, &self.buf[*start..*end]))
+    }
+
+    /// Argument list in the form taken by the command handlers
+    pub fn to_list(&self) -> List<&str, ARGS_MAX> {
+        let mut list = List::new();
+        for arg in self.iter() {
+            list.push(arg);
+        }
+        list
+    }
+}
+
+/// Character cursor over the command line
+pub(crate) struct Lexer<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl<'s> Lexer<'s> {
+    pub(crate) fn new(src: &'s str) -> Lexer<'s> {
+        Lexer { src, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    pub(crate) fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'s str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        &self.src[start..self.pos]
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Variable names start with a letter or underscore followed by letters, digits and underscores
+pub fn is_valid_name(name: &str) -> bool {
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => name.chars().all(is_name_char),
+        _ => false,
+    }
+}
+
+/// Expands the `$` reference at the lexer position into `out`.
+///
+/// Supports `$NAME`, `${NAME}`, `$?`, `$@`, `$#` and the positional `$0` to `$9`,
+/// a `$` not followed by any of them is kept literally.
+pub(crate) fn expand_var<F: Fn(&str, &mut Line)>(
+    lex: &mut Lexer,
+    expand: &F,
+    out: &mut Line,
+) -> Result<(), ParseError> {
+    let name = match lex.peek() {
+        Some('{') => {
+            lex.next();
+            let name = lex.take_while(|c| c != '}');
+            if lex.next() != Some('}') {
+                return Err(ParseError::BadSubstitution);
+            }
+            if !is_valid_name(name) && !matches!(name, "?" | "@" | "#") {
+                return Err(ParseError::BadSubstitution);
+            }
+            name
+        }
+        Some('?' | '@' | '#' | '0'..='9') => {
+            let start = lex.pos;
+            lex.next();
+            &lex.src[start..lex.pos]
+        }
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => lex.take_while(is_name_char),
+        _ => {
+            out.push('$');
+            return Ok(());
+        }
+    };
+
+    expand(name, out);
+    Ok(())
+}
+
+/// Whether `line` ends inside a quote or with a backslash escaping the line break, so
+/// that the command goes on in the next line
+pub(crate) fn is_incomplete(line: &str) -> bool {
+    let mut chars = line.chars();
+    let mut quote = None;
+    let mut word_start = true;
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                if chars.next().is_none() {
+                    return true;
+                }
+            }
+            (Some(_), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '#') if word_start => return false,
+            (None, _) => {}
+        }
+        word_start = quote.is_none() && (c.is_whitespace() || matches!(c, ';' | '|' | '&'));
+    }
+    quote.is_some()
+}
+
+/// Splits a command line into arguments.
+///
+/// Words are separated by whitespace, `'...'` quotes text literally, `"..."` quotes text
+/// but still expands variables and honors `\"`, `\\` and `\$`, a backslash outside of
+/// quotes escapes the next character, a backslash before a line break removes both and
+/// an unquoted `#` starting a word comments out the rest of the line. The word behind an
+/// unquoted `<`, `>` or `>>` names the file of a redirection instead of making up an
+/// argument. Variable references are resolved through `expand`, which appends the value
+/// of the named variable to the buffer.
+pub fn parse_args<F: Fn(&str, &mut Line)>(cmd: &str, expand: F) -> Result<Args, ParseError> {
+    let mut args = Args::new();
+    let mut lex = Lexer { src: cmd, pos: 0 };
+    let mut in_word = false;
+    let mut start = 0;
+    let mut redirect = None;
+
+    while let Some(c) = lex.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    args.end_word(start, &mut redirect);
+                    in_word = false;
+                }
+                start = args.buf.len();
+            }
+            '#' if !in_word => break,
+            '<' | '>' => {
+                if in_word {
+                    args.end_word(start, &mut redirect);
+                    in_word = false;
+                } else if redirect.is_some() {
+                    return Err(ParseError::MissingRedirectTarget);
+                }
+
+                redirect = Some(match c {
+                    '<' => Redirect::Input,
+                    _ if lex.peek() == Some('>') => {
+                        lex.next();
+                        Redirect::Append
+                    }
+                    _ => Redirect::Output,
+                });
+                start = args.buf.len();
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match lex.next() {
+                        Some('\'') => break,
+                        Some(c) => args.buf.push(c),
+                        None => return Err(ParseError::UnterminatedQuote),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match lex.next() {
+                        Some('"') => break,
+                        Some('\\') => match lex.peek() {
+                            Some(c @ ('"' | '\\' | '$')) => {
+                                lex.next();
+                                args.buf.push(c);
+                            }
+                            Some('\n') => {
+                                lex.next();
+                            }
+                            _ => args.buf.push('\\'),
+                        },
+                        Some('$') => expand_var(&mut lex, &expand, &mut args.buf)?,
+                        Some(c) => args.buf.push(c),
+                        None => return Err(ParseError::UnterminatedQuote),
+                    }
+                }
+            }
+            '\\' => match lex.next() {
+                /* a backslash at the end of a line joins it with the next one */
+                Some('\n') => {}
+                Some(c) => {
+                    in_word = true;
+                    args.buf.push(c);
+                }
+                None => in_word = true,
+            },
+            '$' => {
+                /* an unquoted expansion to nothing does not make up a word */
+                expand_var(&mut lex, &expand, &mut args.buf)?;
+                if args.buf.len() > start {
+                    in_word = true;
+                }
+            }
+            c => {
+                in_word = true;
+                args.buf.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        args.end_word(start, &mut redirect);
+    }
+    if redirect.is_some() {
+        return Err(ParseError::MissingRedirectTarget);
+    }
+
+    Ok(args)
+}
@@ -0,0 +1,59 @@
+use super::host;
+use super::shell::Shell;
+use super::term::Terminal;
+use std::string::String;
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+/// Handle printing messages above the line being edited, from any thread.
+///
+/// Messages are queued and shown by `listen`, which moves the half-typed input below them.
+#[derive(Clone, Default)]
+pub struct ExternalPrinter {
+    queue: Arc<Mutex<Vec<String>>>,
+}
+
+impl ExternalPrinter {
+    /// Queues `msg` to be printed, a trailing line feed is added if missing
+    pub fn print<S: Into<String>>(&self, msg: S) {
+        self.queue
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(msg.into());
+        host::wake();
+    }
+
+    fn take(&self) -> Vec<String> {
+        core::mem::take(&mut *self.queue.lock().unwrap_or_else(|err| err.into_inner()))
+    }
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Returns a handle for printing from other threads without garbling the input line
+    pub fn external_printer(&mut self) -> ExternalPrinter {
+        host::init_wake();
+        self.printer
+            .get_or_insert_with(ExternalPrinter::default)
+            .clone()
+    }
+
+    /// Prints the queued messages above the prompt, then draws the prompt and the input again
+    pub(crate) fn print_external(&mut self) {
+        let Some(printer) = &self.printer else {
+            return;
+        };
+        let msgs = printer.take();
+        if msgs.is_empty() {
+            return;
+        }
+
+        self.clear_line();
+        for msg in msgs {
+            for line in msg.lines() {
+                self.print(line);
+                self.print("\n");
+            }
+        }
+        self.refresh_line();
+    }
+}
@@ -0,0 +1,117 @@
+use super::parser::{self, Lexer};
+use super::shell::Shell;
+use super::term::Terminal;
+use super::Line;
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Expands the prompt template, see `set_prompt`. Any other character behind a
+    /// backslash is kept literally, `\t` shows the time as `HH:MM:SS`.
+    pub(crate) fn expand_prompt(&self, template: &str) -> Line {
+        let mut out = Line::new();
+        let mut lex = Lexer::new(template);
+
+        while let Some(c) = lex.next() {
+            match c {
+                '\\' => match lex.next() {
+                    Some('e') => out.push('\x1b'),
+                    #[cfg(feature = "std")]
+                    Some(c @ ('u' | 'h' | 'w' | 't')) => host_segment(c, &mut out),
+                    #[cfg(not(feature = "std"))]
+                    Some('u' | 'h' | 'w' | 't') => {} //unknown without an operating system
+                    Some(c) => out.push(c),
+                    None => out.push('\\'),
+                },
+                '$' => {
+                    /* a bad `${...}` is dropped rather than failing the prompt */
+                    let _ = parser::expand_var(
+                        &mut lex,
+                        &|name, out| self.expand_var(name, out),
+                        &mut out,
+                    );
+                }
+                c => out.push(c),
+            }
+        }
+        out
+    }
+}
+
+/// Number of columns `s` takes on the screen, escape sequences and control characters
+/// take none
+pub(crate) fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                /* csi sequence, e.g. a color, up to its final byte */
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                /* operating system command, e.g. a window title, up to BEL or ST */
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next().is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            c if c.is_control() => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+#[cfg(feature = "std")]
+fn host_segment(c: char, out: &mut Line) {
+    use super::host;
+    use std::env;
+    use std::path::Path;
+
+    match c {
+        'u' => {
+            if let Ok(user) = env::var("USER").or_else(|_| env::var("LOGNAME")) {
+                out.push_str(&user);
+            }
+        }
+        'h' => {
+            let mut name = [0u8; 256];
+            if unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) } == 0
+            {
+                let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                let name = String::from_utf8_lossy(&name[..len]);
+                out.push_str(name.split('.').next().unwrap_or(""));
+            }
+        }
+        'w' => {
+            if let Ok(dir) = env::current_dir() {
+                let home = env::var_os("HOME").filter(|home| !home.is_empty());
+                match home.and_then(|home| dir.strip_prefix(home).ok().map(Path::to_path_buf)) {
+                    Some(rest) if rest.as_os_str().is_empty() => out.push('~'),
+                    Some(rest) => {
+                        out.push_str("~/");
+                        out.push_str(&rest.display().to_string());
+                    }
+                    None => out.push_str(&dir.display().to_string()),
+                }
+            }
+        }
+        't' => {
+            if let Some(tm) = host::local_time(host::unix_time()) {
+                out.push_str(&format!(
+                    "{:02}:{:02}:{:02}",
+                    tm.tm_hour, tm.tm_min, tm.tm_sec
+                ));
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,68 @@
+use super::parser::Args;
+use super::shell::Shell;
+use super::term::Terminal;
+
+#[cfg(feature = "std")]
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Runs a command whose input or output is redirected to files.
+    ///
+    /// Every file is opened before the command runs, so a file that cannot be read or
+    /// created fails the command without running it. Output files are truncated or
+    /// appended to in order, the output itself goes to the last one.
+    pub(crate) fn run_redirected(&mut self, args: &Args) -> i32 {
+        use super::parser::Redirect;
+        use std::fs::{self, File, OpenOptions};
+        use std::io::Write;
+
+        let mut input = None;
+        let mut output = None;
+
+        for (op, path) in args.redirects() {
+            let result = match op {
+                Redirect::Input => fs::read_to_string(path).map(|text| input = Some(text)),
+                Redirect::Output => File::create(path).map(|file| output = Some((file, path))),
+                Redirect::Append => OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .map(|file| output = Some((file, path))),
+            };
+
+            if let Err(err) = result {
+                self.print_error(&format!("{}: {}.", path, err));
+                return 1;
+            }
+        }
+
+        let saved_stdin = input.map(|text| self.streams.stdin.replace(text));
+        let saved_stdout = output
+            .as_ref()
+            .map(|_| self.streams.stdout.replace(Default::default()));
+
+        let mut status = self.exec(&args.to_list());
+
+        if let Some(saved) = saved_stdin {
+            self.streams.stdin = saved;
+        }
+        if let Some(saved) = saved_stdout {
+            let text = core::mem::replace(&mut self.streams.stdout, saved).unwrap_or_default();
+            if let Some((mut file, path)) = output {
+                if let Err(err) = file.write_all(text.as_bytes()) {
+                    self.print_error(&format!("{}: {}.", path, err));
+                    status = 1;
+                }
+            }
+        }
+
+        status
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, T: Terminal> Shell<'a, T> {
+    /* there is no file system to redirect to without the standard library */
+    pub(crate) fn run_redirected(&mut self, _args: &Args) -> i32 {
+        self.print_error("redirection not supported.");
+        1
+    }
+}
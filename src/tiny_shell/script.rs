@@ -0,0 +1,119 @@
+use super::shell::Shell;
+use super::term::Terminal;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/* guards against scripts sourcing themselves */
+const SOURCE_DEPTH_MAX: usize = 16;
+
+/// Settings of the non-interactive mode
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScriptOptions {
+    /// Print every command line behind the prompt before running it
+    pub echo: bool,
+    /// Stop at the first command returning a non-zero exit status
+    pub stop_on_error: bool,
+}
+
+/// Startup file of the interactive shell, `~/.rust_shellrc`
+pub fn default_rc_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| Path::new(&home).join(".rust_shellrc"))
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Runs every line of `reader` through `parse` without the line editor.
+    ///
+    /// Blank lines and lines starting with `#` are skipped and `exit` ends the script.
+    /// Returns the exit status of the last command run, which is the failing one when
+    /// `stop_on_error` is set.
+    pub fn run_script<R: BufRead>(&mut self, reader: R, opts: ScriptOptions) -> io::Result<i32> {
+        self.run_lines(reader, None, opts)
+    }
+
+    /// Runs the script file at `path`, errors of the shell are reported as `file:line`
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P, opts: ScriptOptions) -> io::Result<i32> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        self.run_lines(BufReader::new(file), Some(path), opts)
+    }
+
+    /// Sources the startup file if it exists, returns the status of its last command
+    pub fn load_rc<P: AsRef<Path>>(&mut self, path: P) -> i32 {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return 0;
+        }
+        self.source(path)
+    }
+
+    fn run_lines<R: BufRead>(
+        &mut self,
+        reader: R,
+        name: Option<&Path>,
+        opts: ScriptOptions,
+    ) -> io::Result<i32> {
+        let saved_pos = self.script_pos.take();
+        let mut status = 0;
+        let mut result = Ok(());
+
+        for (n, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+
+            let cmd = line.trim();
+            if cmd.is_empty() || cmd.starts_with('#') {
+                continue;
+            }
+
+            if opts.echo {
+                self.update_prompt();
+                self.print_prompt();
+                self.print(cmd);
+                self.print("\n");
+            }
+
+            self.script_pos = name.map(|name| (name.display().to_string(), n + 1));
+            status = self.run_unexpanded(cmd);
+            if self.exit_status.is_some() || (status != 0 && opts.stop_on_error) {
+                break;
+            }
+        }
+
+        self.script_pos = saved_pos;
+        self.terminal().flush();
+        result.map(|_| status)
+    }
+
+    fn source(&mut self, path: &Path) -> i32 {
+        if self.source_depth >= SOURCE_DEPTH_MAX {
+            self.print_error("source: nested too deeply.");
+            return 1;
+        }
+
+        self.source_depth += 1;
+        let result = self.run_file(path, ScriptOptions::default());
+        self.source_depth -= 1;
+
+        match result {
+            Ok(status) => status,
+            Err(err) => {
+                self.print_error(&format!("source: {}: {}", path.display(), err));
+                1
+            }
+        }
+    }
+
+    pub(crate) fn builtin_source(&mut self, argc: &[&str]) -> i32 {
+        if argc.len() != 2 {
+            self.print_error("usage: source <file>");
+            return 2;
+        }
+        self.source(Path::new(argc[1]))
+    }
+}
@@ -0,0 +1,937 @@
+use super::alias::AliasStack;
+use super::cancel::CancelToken;
+use super::editor::LineEditor;
+use super::highlight::HighlightFn;
+use super::history::History;
+use super::keys::{Key, KeyCode, KeyDecoder, Modifiers};
+use super::parser;
+use super::stream::{write_error, write_term, Io, Streams};
+use super::term::Terminal;
+use super::vars::{Aliases, Vars};
+use super::{Line, List, PipeBuf, CMDS_MAX, ESC_TIMEOUT_MS};
+use core::fmt::Write;
+
+/// Command handler, receives the argument list (command name included), its length and
+/// the streams to read and print through, returns the exit status (zero on success)
+pub type CommandFn = fn(&[&str], usize, &mut Io) -> i32;
+
+/// Exit status reported for a command name that is not registered
+pub const STATUS_UNKNOWN_CMD: i32 = 127;
+
+/// Exit status of a line interrupted by Ctrl+C, 128 plus SIGINT
+pub const STATUS_INTERRUPTED: i32 = 130;
+
+/* prompt of the lines continuing a command after an open quote or a trailing backslash */
+pub(crate) const CONTINUATION_PROMPT: &str = "> ";
+
+/// What `listen` read from the terminal
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)] //returned by value like the line itself
+pub enum Input {
+    /// A command line finished by Enter
+    Line(Line),
+    /// Ctrl+D on an empty line, or the input was closed
+    Eof,
+    /// Ctrl+C abandoned the line
+    Interrupted,
+}
+
+impl Input {
+    /// The command line, `None` for the other events
+    pub fn as_line(&self) -> Option<&str> {
+        match self {
+            Input::Line(line) => Some(line),
+            _ => None,
+        }
+    }
+}
+
+pub struct Shell<'a, T: Terminal> {
+    pub(crate) term: T,
+    decoder: KeyDecoder,
+    pub(crate) cmds: List<(&'a str, CommandFn), CMDS_MAX>,
+    prompt_msg: &'a str,
+    /* the prompt template expanded for the current line, and its width on the screen */
+    prompt: Line,
+    prompt_width: usize,
+    right_prompt_msg: &'a str,
+    right_prompt: Line,
+    right_prompt_width: usize,
+    pub(crate) editor: LineEditor,
+    pub(crate) history: History,
+    pub(crate) history_expansion: bool,
+    history_disp_curr: usize,
+    read_history: bool,
+    typing_preserve: Line,
+    /* rest of the history entry the input is the start of, shown dimmed behind it */
+    pub(crate) suggestion: Line,
+    pub(crate) highlighter: Option<HighlightFn>,
+    /* width of the screen, `None` lets the line run on without wrapping */
+    columns: Option<usize>,
+    pub(crate) rows: Option<usize>,
+    /* text of the status line on the bottom row */
+    pub(crate) status: Option<Line>,
+    /* row of the cursor below the first row of the prompt */
+    cursor_row: usize,
+    pub(crate) vars: Vars,
+    pub(crate) aliases: Aliases,
+    pub(crate) alias_stack: AliasStack,
+    pub(crate) streams: Streams,
+    last_status: i32,
+    exit_on_interrupt: bool,
+    pub(crate) exit_status: Option<i32>,
+    #[cfg(feature = "std")]
+    pub(crate) script_pos: Option<(std::string::String, usize)>,
+    #[cfg(feature = "std")]
+    pub(crate) source_depth: usize,
+    #[cfg(feature = "std")]
+    pub(crate) external: bool,
+    #[cfg(feature = "std")]
+    pub(crate) jobs: std::vec::Vec<super::jobs::Job>,
+    #[cfg(feature = "std")]
+    pub(crate) printer: Option<super::printer::ExternalPrinter>,
+    #[cfg(feature = "std")]
+    pub(crate) status_handle: Option<super::status::StatusHandle>,
+    /* whether the words highlighted on this line name a program in `PATH` */
+    #[cfg(feature = "std")]
+    pub(crate) path_cache: core::cell::RefCell<std::vec::Vec<(std::string::String, bool)>>,
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    pub fn new(prompt_msg: &'a str, term: T) -> Shell<'a, T> {
+        Shell {
+            term,
+            decoder: KeyDecoder::new(),
+            cmds: List::new(),
+            prompt_msg,
+            prompt: Line::new(),
+            prompt_width: 0,
+            right_prompt_msg: "",
+            right_prompt: Line::new(),
+            right_prompt_width: 0,
+            editor: LineEditor::new(),
+            history: History::new(),
+            history_expansion: true,
+            history_disp_curr: 0,
+            read_history: false,
+            typing_preserve: Line::new(),
+            suggestion: Line::new(),
+            highlighter: None,
+            columns: None,
+            rows: None,
+            status: None,
+            cursor_row: 0,
+            vars: Vars::new(),
+            aliases: Aliases::new(),
+            alias_stack: AliasStack::new(),
+            streams: Streams::default(),
+            last_status: 0,
+            exit_on_interrupt: false,
+            exit_status: None,
+            #[cfg(feature = "std")]
+            script_pos: None,
+            #[cfg(feature = "std")]
+            source_depth: 0,
+            #[cfg(feature = "std")]
+            external: false,
+            #[cfg(feature = "std")]
+            jobs: std::vec::Vec::new(),
+            #[cfg(feature = "std")]
+            printer: None,
+            #[cfg(feature = "std")]
+            status_handle: None,
+            #[cfg(feature = "std")]
+            path_cache: Default::default(),
+        }
+    }
+
+    pub fn add_command(&mut self, cmd_name: &'a str, cmd_func: CommandFn) {
+        match self.cmds.iter_mut().find(|(name, _)| *name == cmd_name) {
+            Some(cmd) => cmd.1 = cmd_func,
+            None => self.cmds.push((cmd_name, cmd_func)),
+        }
+    }
+
+    /// Prompt template, see `set_prompt`
+    pub fn prompt(&self) -> &'a str {
+        self.prompt_msg
+    }
+
+    /// Changes the prompt template, which is expanded again before every line.
+    ///
+    /// `$NAME`, `${NAME}` and `$?` expand to shell variables, `\u`, `\h`, `\w` and `\t` to
+    /// the user, host, working directory and time, and `\e` starts a color sequence, e.g.
+    /// `"\\e[32m$DEVICE\\e[0m \\w > "`. Escape sequences take no room when the line
+    /// is laid out.
+    pub fn set_prompt(&mut self, template: &'a str) {
+        self.prompt_msg = template;
+    }
+
+    /// Shows a second prompt template at the right edge, e.g. a clock or the connection
+    /// state. It is hidden while the input would reach it, an empty template removes it.
+    pub fn set_right_prompt(&mut self, template: &'a str) {
+        self.right_prompt_msg = template;
+    }
+
+    /// Expands the prompt templates for the next line
+    pub(crate) fn update_prompt(&mut self) {
+        self.prompt = self.expand_prompt(self.prompt_msg);
+        self.prompt_width = super::prompt::display_width(&self.prompt);
+        self.right_prompt = self.expand_prompt(self.right_prompt_msg);
+        self.right_prompt_width = super::prompt::display_width(&self.right_prompt);
+    }
+
+    /* prints the right prompt if the line `end` columns long leaves room for it on the
+     * first row, then puts the cursor back to `pos` */
+    fn print_right_prompt(&mut self, pos: usize, end: usize) -> bool {
+        let Some(width) = self.columns else {
+            return false;
+        };
+        if self.right_prompt_width == 0 || end + 1 + self.right_prompt_width > width {
+            return false;
+        }
+
+        write!(self.term, "\x1b[{}C", width - end - self.right_prompt_width);
+        self.term.write(self.right_prompt.as_bytes());
+        self.print("\r");
+        if pos > 0 {
+            write!(self.term, "\x1b[{}C", pos);
+        }
+        true
+    }
+
+    pub(crate) fn print_prompt(&mut self) {
+        self.term.write(self.prompt.as_bytes());
+    }
+
+    pub fn stop(&mut self) {
+        self.set_status(None);
+        self.term.flush();
+        self.term.disable_raw_mode();
+    }
+
+    pub fn terminal(&mut self) -> &mut T {
+        &mut self.term
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Shell variables, `$NAME` falls back to the process environment for names not set here
+    pub fn vars(&mut self) -> &mut Vars {
+        &mut self.vars
+    }
+
+    /// Aliases consulted before the builtins and the registered commands
+    pub fn aliases(&mut self) -> &mut Aliases {
+        &mut self.aliases
+    }
+
+    /// Exit status of the last command, `$?`
+    pub fn last_status(&self) -> i32 {
+        self.last_status
+    }
+
+    /// Token raised by Ctrl+C while a command runs, clones may raise it from elsewhere
+    pub fn cancel_token(&self) -> CancelToken {
+        self.streams.cancel.clone()
+    }
+
+    /// Makes Ctrl+C at the prompt end `run` instead of only abandoning the line
+    pub fn set_exit_on_interrupt(&mut self, exit: bool) {
+        self.exit_on_interrupt = exit;
+    }
+
+    /// Status given to the `exit` builtin, `None` until it runs
+    pub fn exit_status(&self) -> Option<i32> {
+        self.exit_status
+    }
+
+    /// Appends the value of the variable `name` to `out`, unset variables expand to nothing
+    pub(crate) fn expand_var(&self, name: &str, out: &mut Line) {
+        if name == "?" {
+            let _ = write!(out, "{}", self.last_status);
+        } else if let Some(value) = self.vars.get(name) {
+            out.push_str(value);
+        } else {
+            #[cfg(feature = "std")]
+            if let Ok(value) = std::env::var(name) {
+                out.push_str(&value);
+            }
+        }
+    }
+
+    fn getc(&mut self) -> Option<u8> {
+        /* input typed while the last command ran comes first */
+        if !self.streams.typeahead.is_empty() {
+            return Some(self.streams.typeahead.remove(0));
+        }
+
+        self.term.flush();
+        self.term.read_byte()
+    }
+
+    fn getc_timeout(&mut self, timeout_ms: u32) -> Option<u8> {
+        if !self.streams.typeahead.is_empty() {
+            return Some(self.streams.typeahead.remove(0));
+        }
+
+        self.term.read_byte_timeout(timeout_ms)
+    }
+
+    /* prints `s` with every line feed turned into the line ending of the terminal */
+    pub(crate) fn print(&mut self, s: &str) {
+        write_term(&mut self.term, s);
+    }
+
+    /// Prints an error raised by the shell itself, prefixed by the `file:line` of a running script
+    pub(crate) fn print_error(&mut self, msg: &str) {
+        #[cfg(feature = "std")]
+        if let Some((name, line)) = &self.script_pos {
+            let pos = format!("{}:{}: ", name, line);
+            self.term.write_error(pos.as_bytes());
+        }
+
+        write_error(&mut self.term, msg);
+    }
+
+    /* columns of the screen, treated as endless when unknown */
+    fn width(&self) -> usize {
+        /* without a known width lines run on, as on a screen wider than any of them */
+        self.columns.unwrap_or(u16::MAX as usize)
+    }
+
+    /* screen position of input position `n` as `row * width + column`, counted from the
+     * prompt start, each line of a multi-line input starting a row behind the previous one */
+    fn input_offset(&self, n: usize) -> usize {
+        let width = self.width();
+        let mut offset = self.prompt_width;
+        let mut row_start = 0;
+
+        for &c in &self.editor.chars()[..n] {
+            if c != '\n' {
+                offset += 1;
+                continue;
+            }
+
+            /* a line filling its last row exactly has already moved on to the next one */
+            if offset == row_start || !offset.is_multiple_of(width) {
+                offset = (offset / width + 1) * width;
+            }
+            row_start = offset;
+            offset += CONTINUATION_PROMPT.chars().count();
+        }
+        offset
+    }
+
+    /* screen positions of the cursor and the end of the input, counted from the prompt start */
+    fn line_positions(&self) -> (usize, usize) {
+        (
+            self.input_offset(self.editor.cursor_pos()),
+            self.input_offset(self.editor.char_cnt()),
+        )
+    }
+
+    pub(crate) fn new_line(&mut self) {
+        self.hide_suggestion();
+
+        /* shift the cursor to the line end before switching the new line,
+         * otherwise the the user input might be cut */
+        let (pos, end) = self.line_positions();
+        let end_row = end / self.width();
+        if end_row > self.cursor_row {
+            write!(self.term, "\x1b[{}B", end_row - self.cursor_row);
+        } else if end > pos {
+            write!(self.term, "\x1b[{}C", end - pos);
+        }
+
+        /* a line filling the last row exactly has already moved on to the next one */
+        if end > 0 && end.is_multiple_of(self.width()) {
+            self.print("\r");
+        } else {
+            self.print("\n");
+        }
+        self.cursor_row = 0;
+    }
+
+    /// Erases the prompt and the input, including the rows they wrap onto
+    pub(crate) fn clear_line(&mut self) {
+        if self.cursor_row > 0 {
+            write!(self.term, "\x1b[{}A", self.cursor_row);
+        }
+        self.print("\r\x1b[J");
+        self.cursor_row = 0;
+    }
+
+    pub(crate) fn refresh_line(&mut self) {
+        self.update_suggestion();
+        self.draw_line();
+    }
+
+    /// Draws the prompt and the input again, after the suggestion was updated
+    pub(crate) fn draw_line(&mut self) {
+        self.clear_line();
+        self.draw_status();
+
+        /* print prompt message */
+        self.print_prompt();
+
+        /* print user input, followed by the dimmed suggestion */
+        self.print_input();
+        if !self.suggestion.is_empty() {
+            write!(self.term, "\x1b[2m{}\x1b[0m", self.suggestion);
+        }
+
+        let (pos, end) = self.line_positions();
+        let end = end + self.suggestion.chars().count();
+        if self.print_right_prompt(pos, end) {
+            self.cursor_row = 0;
+            return;
+        }
+
+        /* the cursor waits behind the last column until the next character, wrap it now */
+        let width = self.width();
+        if end > 0 && end.is_multiple_of(width) {
+            self.print("\n");
+        }
+
+        /* shift cursor position */
+        let (row, col) = (pos / width, pos % width);
+        let end_row = end / width;
+        if end_row > row {
+            write!(self.term, "\x1b[{}A\r", end_row - row);
+            if col > 0 {
+                write!(self.term, "\x1b[{}C", col);
+            }
+        } else if end > pos {
+            write!(self.term, "\x1b[{}D", end - pos);
+        }
+        self.cursor_row = row;
+    }
+
+    fn cursor_shift_one_left(&mut self) {
+        if self.editor.cursor_shift_one_left() {
+            self.refresh_line();
+        }
+    }
+
+    fn cursor_shift_one_right(&mut self) {
+        if self.editor.cursor_shift_one_right() {
+            self.refresh_line();
+        }
+    }
+
+    /* moves between the lines of a multi-line input, or through the history from its first
+     * and last line */
+    fn cursor_line_up(&mut self) {
+        if self.editor.cursor_line_up() {
+            self.refresh_line();
+        } else {
+            self.history_arrow_up();
+        }
+    }
+
+    fn cursor_line_down(&mut self) {
+        if self.editor.cursor_line_down() {
+            self.refresh_line();
+        } else {
+            self.history_arrow_down();
+        }
+    }
+
+    fn reset_history_tracking(&mut self) {
+        self.history_disp_curr = 0;
+        self.read_history = false;
+    }
+
+    fn preserve_current_typing(&mut self) {
+        self.typing_preserve = self.editor.text();
+    }
+
+    fn restore_user_typing(&mut self) {
+        /* restore the user typing by overwriting the buffer */
+        self.editor.set_text(&self.typing_preserve);
+    }
+
+    fn display_history(&mut self, n: usize) {
+        /* display the command by overwriting the buffer */
+        if let Some(cmd) = self.history.get(n) {
+            self.editor.set_text(cmd);
+        }
+    }
+
+    /* whether the `n`-th latest entry starts with the typing and differs from the line shown */
+    fn history_matches(&self, n: usize) -> bool {
+        let shown = self.editor.text();
+        self.history
+            .get(n)
+            .is_some_and(|cmd| cmd.starts_with(&self.typing_preserve[..]) && cmd != &shown[..])
+    }
+
+    fn history_arrow_up(&mut self) {
+        /* ignore the event if no command is stored in the history */
+        if self.history.is_empty() {
+            return;
+        }
+
+        /* set up the flag to indicate the user triggered the history reading */
+        if !self.read_history {
+            self.preserve_current_typing(); //save current input words
+            self.history_disp_curr = 0; //counter set zero (i.e., read from the latest record)
+            self.read_history = true; //history reading is on
+        }
+
+        /* step only through the older commands starting with what the user typed */
+        match (self.history_disp_curr..self.history.len()).find(|&n| self.history_matches(n)) {
+            Some(n) => {
+                self.display_history(n);
+                self.history_disp_curr = n + 1;
+            }
+            None => {
+                /* restore user's typing if the whole list has been traversed */
+                self.restore_user_typing();
+                self.reset_history_tracking();
+            }
+        }
+
+        self.refresh_line();
+    }
+
+    fn history_arrow_down(&mut self) {
+        /* ignore the event before the up arrow is first pressed */
+        if !self.read_history {
+            return;
+        }
+
+        match (0..self.history_disp_curr - 1)
+            .rev()
+            .find(|&n| self.history_matches(n))
+        {
+            Some(n) => {
+                /* display a newer command from the history */
+                self.display_history(n);
+                self.history_disp_curr = n + 1;
+            }
+            None => {
+                /* restore user's typing if the whole list has been traversed */
+                self.restore_user_typing();
+                self.reset_history_tracking();
+            }
+        }
+
+        self.refresh_line();
+    }
+
+    fn read_key(&mut self) -> Option<Key> {
+        let mut c = self.getc()?;
+
+        loop {
+            if let Some(key) = self.decoder.feed(c) {
+                return Some(key);
+            }
+
+            /* wait shortly for the rest of the sequence, a lone escape never gets one */
+            c = match self.getc_timeout(ESC_TIMEOUT_MS) {
+                Some(c) => c,
+                None => match self.decoder.timeout() {
+                    Some(key) => return Some(key),
+                    None => self.getc()?,
+                },
+            };
+        }
+    }
+
+    fn delete_char_under_cursor(&mut self) {
+        if self.editor.char_cnt() != 0 && self.editor.cursor_pos() != self.editor.char_cnt() {
+            self.editor.remove_char(self.editor.cursor_pos() + 1, true);
+            self.refresh_line();
+        }
+    }
+
+    /// Reads one command line.
+    ///
+    /// Ctrl+C abandons the line, Ctrl+D on an empty line or closing the input ends it.
+    pub fn listen(&mut self) -> Input {
+        #[cfg(feature = "std")]
+        self.report_jobs();
+
+        self.columns = self.term.columns().filter(|&cols| cols > 0);
+        self.rows = self.term.rows();
+        self.update_prompt();
+
+        /* redraw a line left unfinished by the previous call, otherwise start a fresh prompt */
+        if self.editor.char_cnt() > 0 {
+            self.refresh_line();
+        } else {
+            /* programs may have come and gone since the last line */
+            #[cfg(feature = "std")]
+            self.path_cache.get_mut().clear();
+
+            self.draw_status();
+            self.print_prompt();
+            self.print_right_prompt(self.prompt_width, self.prompt_width);
+        }
+
+        loop {
+            #[cfg(feature = "std")]
+            {
+                self.print_external();
+                self.apply_status_update();
+            }
+
+            let key = match self.read_key() {
+                Some(key) => key,
+                None => return Input::Eof,
+            };
+
+            match key.code {
+                KeyCode::Char(c) if key.mods.contains(Modifiers::CTRL) => match c {
+                    'a' => {
+                        self.editor.cursor_home();
+                        self.refresh_line();
+                    }
+                    'b' => self.cursor_shift_one_left(),
+                    'c' => {
+                        /* leave the abandoned line on the screen marked by ^C */
+                        self.editor.cursor_end();
+                        self.suggestion.clear();
+                        self.draw_line();
+                        self.print("^C\n");
+                        self.cursor_row = 0;
+                        self.editor.clear();
+                        self.reset_history_tracking();
+                        self.last_status = STATUS_INTERRUPTED;
+                        return Input::Interrupted;
+                    }
+                    'd' => {
+                        if self.editor.char_cnt() == 0 {
+                            self.print("\n");
+                            return Input::Eof;
+                        }
+                        self.delete_char_under_cursor();
+                    }
+                    'e' => {
+                        if self.editor.char_cnt() > 0 {
+                            self.editor.cursor_end();
+                            self.refresh_line();
+                        }
+                    }
+                    'f' => {
+                        if !self.accept_suggestion(false) {
+                            self.cursor_shift_one_right();
+                        }
+                    }
+                    'u' => {
+                        self.editor.clear();
+                        self.refresh_line();
+                    }
+                    _ => continue, //unused control keys
+                },
+                KeyCode::Char(c) if !key.mods.contains(Modifiers::ALT) => {
+                    if !self.editor.is_full() {
+                        self.read_history = false;
+                        self.editor.insert_char(c);
+                        self.refresh_line();
+                    }
+                }
+                KeyCode::Enter if parser::is_incomplete(&self.editor.text()) => {
+                    /* the command goes on in a new line of the same input */
+                    if !self.editor.is_full() {
+                        self.read_history = false;
+                        self.editor.cursor_end();
+                        self.editor.insert_char('\n');
+                        self.refresh_line();
+                    }
+                }
+                KeyCode::Enter => {
+                    /* reset the history tracking so the command is placed chronologically */
+                    self.reset_history_tracking();
+
+                    /* generate the command string for function return */
+                    let cmd = self.editor.text();
+
+                    /* push command to the history if it is not empty */
+                    if self.editor.char_cnt() > 0 {
+                        self.history.push(&cmd);
+                    }
+
+                    /* move to next line */
+                    self.new_line();
+                    self.editor.clear();
+
+                    return Input::Line(cmd);
+                }
+                KeyCode::Tab if key.mods == Modifiers::NONE => self.complete(),
+                KeyCode::Resize(cols, rows) => {
+                    /* reflow the line for the new width and move the status line down */
+                    self.columns = Some(cols as usize).filter(|&cols| cols > 0);
+                    self.rows = Some(rows as usize);
+                    self.set_scroll_region();
+                    self.refresh_line();
+                }
+                KeyCode::Up => self.cursor_line_up(),
+                KeyCode::Down => self.cursor_line_down(),
+                KeyCode::PageUp => self.history_arrow_up(),
+                KeyCode::PageDown => self.history_arrow_down(),
+                KeyCode::Right => {
+                    if !self.accept_suggestion(false) {
+                        self.cursor_shift_one_right();
+                    }
+                }
+                KeyCode::Char('f') if key.mods == Modifiers::ALT => {
+                    self.accept_suggestion(true);
+                }
+                KeyCode::Left => self.cursor_shift_one_left(),
+                KeyCode::Home => {
+                    self.editor.cursor_home();
+                    self.refresh_line();
+                }
+                KeyCode::End => {
+                    if self.editor.char_cnt() > 0 {
+                        self.editor.cursor_end();
+                        self.refresh_line();
+                    }
+                }
+                KeyCode::Delete => self.delete_char_under_cursor(),
+                KeyCode::Backspace => {
+                    if (self.editor.char_cnt() != 0) && (self.editor.cursor_pos() != 0) {
+                        self.editor.remove_char(self.editor.cursor_pos(), false);
+                        self.refresh_line();
+                    }
+                }
+                _ => continue, //keys without binding
+            };
+        }
+    }
+
+    /// Runs a command line and returns its exit status, history references in it are
+    /// expanded first and the expanded line is echoed
+    pub fn parse(&mut self, cmd: &str) -> i32 {
+        if self.history_expansion {
+            match self.expand_history(cmd) {
+                Ok(Some(line)) => {
+                    for part in line.split('\n') {
+                        self.print(part);
+                        self.print("\n");
+                    }
+                    self.history.replace_latest(cmd, &line);
+                    return self.run_unexpanded(&line);
+                }
+                Ok(None) => {}
+                Err((event, reason)) => {
+                    self.history.pop_latest_if(cmd);
+                    let mut msg = Line::from(event);
+                    msg.push_str(reason);
+                    self.print_error(&msg);
+                    self.last_status = 1;
+                    return 1;
+                }
+            }
+        }
+
+        self.run_unexpanded(cmd)
+    }
+
+    /// Runs a command line as it is and returns its exit status
+    pub(crate) fn run_unexpanded(&mut self, cmd: &str) -> i32 {
+        self.streams.cancel.reset();
+        let status = self.run_line(cmd);
+
+        if self.streams.cancel.is_cancelled() {
+            self.print("^C\n");
+            self.streams.cancel.reset();
+        }
+        status
+    }
+
+    /// Reads and runs command lines until `exit`, the end of the input or, if set by
+    /// `set_exit_on_interrupt`, Ctrl+C, then restores the terminal and returns the exit status
+    pub fn run(&mut self) -> i32 {
+        self.term.enable_raw_mode();
+
+        let status = loop {
+            /* `exit` may already have run from a startup file */
+            if let Some(status) = self.exit_status.take() {
+                break status;
+            }
+
+            match self.listen() {
+                Input::Line(cmd) => {
+                    self.parse(&cmd);
+                }
+                Input::Interrupted if !self.exit_on_interrupt => {}
+                Input::Interrupted | Input::Eof => break self.last_status,
+            }
+        };
+
+        self.stop();
+        status
+    }
+
+    /// Runs the commands of a line joined by `;`, `&&` and `||`
+    pub(crate) fn run_line(&mut self, line: &str) -> i32 {
+        /* reject syntax errors before anything runs */
+        if let Err(err) = parser::check_syntax(line) {
+            self.print_error(err.message());
+            self.last_status = 2;
+            return 2;
+        }
+
+        for (op, cmd) in parser::split_chain(line).flatten() {
+            if op.is_none_or(|op| op.should_run(self.last_status)) {
+                self.last_status = match parser::split_background(cmd) {
+                    (cmd, true) => self.run_background(cmd),
+                    (cmd, false) => self.run_pipeline(cmd),
+                };
+            }
+
+            /* `exit` and Ctrl+C drop the rest of the line */
+            if self.exit_status.is_some() {
+                break;
+            }
+
+            if self.streams.poll_interrupt(&mut self.term) {
+                self.last_status = STATUS_INTERRUPTED;
+                break;
+            }
+        }
+
+        self.last_status
+    }
+
+    /* jobs need threads and processes of the host */
+    #[cfg(not(feature = "std"))]
+    fn run_background(&mut self, _cmd: &str) -> i32 {
+        self.print_error("background jobs not supported.");
+        1
+    }
+
+    /// Runs the stages of `a | b | c`, each one reading the output of the previous one,
+    /// and returns the status of the last one
+    fn run_pipeline(&mut self, cmd: &str) -> i32 {
+        /* the pipeline as a whole reads and writes the streams it was given */
+        let mut outer_stdin = None;
+        let mut outer_stdout = self.streams.stdout.take();
+        let mut piped = false;
+
+        let mut stages = parser::split_pipeline(cmd).flatten().peekable();
+        let mut status = self.last_status;
+        while let Some(stage) = stages.next() {
+            let last = stages.peek().is_none();
+            self.streams.stdout = if last {
+                outer_stdout.take()
+            } else {
+                Some(PipeBuf::new())
+            };
+
+            status = self.run_command(stage);
+
+            if last {
+                outer_stdout = self.streams.stdout.take();
+            } else {
+                let output = self.streams.stdout.take();
+                let input = core::mem::replace(&mut self.streams.stdin, output);
+                if !piped {
+                    outer_stdin = input;
+                    piped = true;
+                }
+            }
+        }
+
+        if piped {
+            self.streams.stdin = outer_stdin;
+        }
+        self.streams.stdout = outer_stdout;
+        status
+    }
+
+    pub(crate) fn run_command(&mut self, cmd: &str) -> i32 {
+        /* split string into list of arguments, expanding the variables */
+        let args = match parser::parse_args(cmd, |name, out| self.expand_var(name, out)) {
+            Ok(args) => args,
+            Err(err) => {
+                self.print_error(err.message());
+                return 2;
+            }
+        };
+
+        if args.redirects().next().is_some() {
+            return self.run_redirected(&args);
+        }
+
+        self.exec(&args.to_list())
+    }
+
+    /// Runs the alias, builtin or registered command named by `argc[0]`
+    pub(crate) fn exec(&mut self, argc: &[&str]) -> i32 {
+        /* get first element of the argc list */
+        let argc_0 = match argc.first() {
+            Some(cmd_name) => *cmd_name,     //get string of the argc[0]
+            None => return self.last_status, //empty string
+        };
+
+        if let Some(status) = self.run_alias(argc) {
+            return status;
+        }
+
+        if let Some(status) = self.run_builtin(argc) {
+            return status;
+        }
+
+        /* match command */
+        match self.cmds.iter().find(|(name, _)| *name == argc_0) {
+            Some((_, cmd_func)) => self.call_command(*cmd_func, argc),
+            None => {
+                #[cfg(feature = "std")]
+                if let Some(status) = self.run_external(argc) {
+                    return status;
+                }
+
+                self.print_error("unknown command.");
+                STATUS_UNKNOWN_CMD
+            }
+        }
+    }
+
+    /* a panic in the handler is reported like an error instead of ending the shell */
+    #[cfg(feature = "std")]
+    fn call_command(&mut self, cmd_func: CommandFn, argc: &[&str]) -> i32 {
+        let mut io = self.streams.io(&mut self.term);
+        match super::host::catch_panic(|| cmd_func(argc, argc.len(), &mut io)) {
+            Ok(status) => status,
+            Err(msg) => {
+                self.print_error(&std::format!("{}: panicked: {}.", argc[0], msg));
+                1
+            }
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn call_command(&mut self, cmd_func: CommandFn, argc: &[&str]) -> i32 {
+        cmd_func(argc, argc.len(), &mut self.streams.io(&mut self.term))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Shell<'a, super::StdTerminal> {
+    /// Enters raw mode, the returned guard restores the terminal when dropped.
+    ///
+    /// A panic restores the terminal before its message is printed as well.
+    pub fn start(&mut self) -> super::TerminalGuard {
+        self.term.enable_raw_mode();
+        super::TerminalGuard::new()
+    }
+
+    pub fn puts(s: &str) {
+        use std::io::Write;
+
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(s.as_bytes());
+        let _ = stdout.flush();
+    }
+
+    pub fn cls() {
+        Shell::puts("\x1b[2J\x1b[H");
+    }
+}
@@ -0,0 +1,133 @@
+use super::shell::Shell;
+use super::term::Terminal;
+use super::Line;
+#[cfg(feature = "std")]
+use {
+    super::host,
+    std::string::String,
+    std::sync::{Arc, Mutex},
+};
+
+/// Handle changing the status line and redrawing the prompts, from any thread.
+///
+/// The changes are drawn by `listen`, right away while it waits for a key.
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+pub struct StatusHandle {
+    update: Arc<Mutex<StatusUpdate>>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct StatusUpdate {
+    /* the new status text if it changed, `Some(None)` removes the line */
+    status: Option<Option<String>>,
+    prompt: bool,
+}
+
+#[cfg(feature = "std")]
+impl StatusHandle {
+    /// Shows `text` on the bottom row like `Shell::set_status`, `None` removes it again
+    pub fn set_status(&self, text: Option<&str>) {
+        self.lock().status = Some(text.map(String::from));
+        host::wake();
+    }
+
+    /// Expands the prompt templates again, e.g. for a clock in the right prompt
+    pub fn refresh_prompt(&self) {
+        self.lock().prompt = true;
+        host::wake();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StatusUpdate> {
+        self.update.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Shows `text` on the bottom row of the terminal, `None` removes it again.
+    ///
+    /// The lines above scroll without touching it, so the application may update it at
+    /// any time, e.g. with the state of a connection. Needs a terminal reporting `rows`.
+    pub fn set_status(&mut self, text: Option<&str>) {
+        match text {
+            Some(text) => {
+                let shown = self.status.is_some();
+                self.status = Some(Line::from(text));
+                if !shown {
+                    if self.rows.is_none() {
+                        self.rows = self.term.rows();
+                    }
+
+                    /* make room below the cursor in case it sits on the bottom row, a bare
+                     * line feed keeps the column */
+                    self.term.write(b"\n\x1b[1A");
+                    self.set_scroll_region();
+                }
+                self.draw_status();
+            }
+            None => {
+                if self.status.take().is_some() {
+                    self.print("\x1b7\x1b[r");
+                    if let Some(rows) = self.rows {
+                        write!(self.term, "\x1b[{};1H\x1b[2K", rows);
+                    }
+                    self.print("\x1b8");
+                }
+            }
+        }
+        self.term.flush();
+    }
+
+    /// Keeps the scrolling text off the bottom row while the status line is shown
+    pub(crate) fn set_scroll_region(&mut self) {
+        match (&self.status, self.rows) {
+            (Some(_), Some(rows)) if rows > 1 => {
+                write!(self.term, "\x1b7\x1b[1;{}r\x1b8", rows - 1);
+                self.draw_status();
+            }
+            _ => {}
+        }
+    }
+
+    /// Redraws the status line, without wrapping and leaving the cursor where it was
+    pub(crate) fn draw_status(&mut self) {
+        let (Some(status), Some(rows)) = (&self.status, self.rows) else {
+            return;
+        };
+        if rows < 2 {
+            return;
+        }
+
+        write!(self.term, "\x1b7\x1b[{};1H\x1b[2K\x1b[?7l", rows);
+        self.term.write(status.as_bytes());
+        self.term.write(b"\x1b[?7h\x1b8");
+    }
+
+    /// Returns a handle for changing the status line and the prompts from other threads
+    #[cfg(feature = "std")]
+    pub fn status_handle(&mut self) -> StatusHandle {
+        host::init_wake();
+        self.status_handle
+            .get_or_insert_with(StatusHandle::default)
+            .clone()
+    }
+
+    /// Applies the changes made through the status handle and redraws what they touch
+    #[cfg(feature = "std")]
+    pub(crate) fn apply_status_update(&mut self) {
+        let Some(handle) = &self.status_handle else {
+            return;
+        };
+        let update = core::mem::take(&mut *handle.lock());
+
+        if let Some(text) = update.status {
+            self.set_status(text.as_deref());
+        }
+        if update.prompt {
+            self.update_prompt();
+            self.draw_line();
+            self.term.flush();
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use super::cancel::CancelToken;
+use super::term::Terminal;
+use super::{List, PipeBuf, TYPEAHEAD_MAX};
+use core::fmt;
+
+/* Ctrl+C as read from the terminal in raw mode */
+const CTRL_C: u8 = 0x03;
+
+/// Input typed while a command runs, kept for the next prompt
+pub(crate) type Typeahead = List<u8, TYPEAHEAD_MAX>;
+
+/// Input and output redirection of the command being run, and how to interrupt it
+#[derive(Default)]
+pub(crate) struct Streams {
+    /// Output of the previous pipeline stage, `None` when reading from the terminal
+    pub(crate) stdin: Option<PipeBuf>,
+    /// Buffer collecting the output for the next stage, `None` when writing to the terminal
+    pub(crate) stdout: Option<PipeBuf>,
+    pub(crate) typeahead: Typeahead,
+    pub(crate) cancel: CancelToken,
+}
+
+impl Streams {
+    pub(crate) fn io<'i>(&'i mut self, term: &'i mut dyn Terminal) -> Io<'i> {
+        Io {
+            stdin: self.stdin.as_deref(),
+            stdout: self.stdout.as_mut(),
+            typeahead: &mut self.typeahead,
+            cancel: &self.cancel,
+            term,
+        }
+    }
+
+    /// Reads the input waiting on the terminal into the typeahead, raising the cancel
+    /// token on Ctrl+C, and tells whether it is raised
+    pub(crate) fn poll_interrupt(&mut self, term: &mut dyn Terminal) -> bool {
+        poll_interrupt(term, &mut self.typeahead, &self.cancel)
+    }
+}
+
+fn poll_interrupt(
+    term: &mut dyn Terminal,
+    typeahead: &mut Typeahead,
+    cancel: &CancelToken,
+) -> bool {
+    while typeahead.len() < TYPEAHEAD_MAX {
+        match term.poll_byte() {
+            Some(CTRL_C) => {
+                /* like a tty, the interrupt discards what was typed before it */
+                typeahead.clear();
+                cancel.cancel();
+            }
+            Some(c) => typeahead.push(c),
+            None => break,
+        }
+    }
+    cancel.is_cancelled()
+}
+
+/// Standard streams handed to a command.
+///
+/// Output goes to the terminal or, inside a pipeline, to the input of the next command.
+/// Lines end with a plain `\n`, the carriage return the raw-mode terminal needs is added
+/// when writing to it. Errors always go to the terminal.
+pub struct Io<'i> {
+    stdin: Option<&'i str>,
+    stdout: Option<&'i mut PipeBuf>,
+    typeahead: &'i mut Typeahead,
+    cancel: &'i CancelToken,
+    term: &'i mut dyn Terminal,
+}
+
+impl<'i> Io<'i> {
+    /// Text piped into the command, `None` when the input is the terminal
+    pub fn input(&self) -> Option<&'i str> {
+        self.stdin
+    }
+
+    /// Lines of the piped input, nothing when the input is the terminal
+    pub fn lines(&self) -> core::str::Lines<'i> {
+        self.stdin.unwrap_or("").lines()
+    }
+
+    /// Whether the output is collected for the next command instead of shown
+    pub fn is_piped(&self) -> bool {
+        self.stdout.is_some()
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        match &mut self.stdout {
+            Some(pipe) => pipe.push_str(s),
+            None => write_term(self.term, s),
+        }
+    }
+
+    /// Whether Ctrl+C was pressed since the command started, long running commands
+    /// should poll it and return early
+    pub fn is_cancelled(&mut self) -> bool {
+        poll_interrupt(self.term, self.typeahead, self.cancel)
+    }
+
+    /// Prints a message to the terminal even when the output is piped
+    pub fn error(&mut self, msg: &str) {
+        write_error(self.term, msg);
+    }
+
+    /// Gives direct access to the terminal, e.g. for screen control sequences
+    pub fn terminal(&mut self) -> &mut dyn Terminal {
+        self.term
+    }
+
+    /* allows `write!(io, ...)` without an intermediate string */
+    pub fn write_fmt(&mut self, args: fmt::Arguments) {
+        let _ = fmt::write(self, args);
+    }
+}
+
+impl fmt::Write for Io<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        Io::write_str(self, s);
+        Ok(())
+    }
+}
+
+/* writes `s` turning every line feed into the line ending of the terminal */
+pub(crate) fn write_term(term: &mut dyn Terminal, s: &str) {
+    let newline = term.newline();
+    for (i, line) in s.split('\n').enumerate() {
+        if i > 0 {
+            term.write(newline);
+        }
+        term.write(line.as_bytes());
+    }
+}
+
+/* writes the lines of `msg` to the error output of the terminal */
+pub(crate) fn write_error(term: &mut dyn Terminal, msg: &str) {
+    let newline = term.newline();
+    for line in msg.split('\n') {
+        term.write_error(line.as_bytes());
+        term.write_error(newline);
+    }
+}
@@ -0,0 +1,54 @@
+use super::shell::Shell;
+use super::term::Terminal;
+use super::Line;
+
+impl<'a, T: Terminal> Shell<'a, T> {
+    /// Looks up the latest history entry continuing the input, shown while the cursor
+    /// is at the end of the line
+    pub(crate) fn update_suggestion(&mut self) {
+        self.suggestion.clear();
+        if self.editor.char_cnt() == 0 || self.editor.cursor_pos() != self.editor.char_cnt() {
+            return;
+        }
+
+        let text = self.editor.text();
+        if let Some(cmd) = self.history.iter().rev().find(|cmd| {
+            /* the dimmed text stays in the row of the cursor */
+            cmd.len() > text.len() && cmd.starts_with(&*text) && !cmd[text.len()..].contains('\n')
+        }) {
+            self.suggestion.push_str(&cmd[text.len()..]);
+        }
+    }
+
+    /// Takes over the suggestion into the input, all of it or up to the end of its next
+    /// word, returns false if there is none
+    pub(crate) fn accept_suggestion(&mut self, word: bool) -> bool {
+        if self.suggestion.is_empty() {
+            return false;
+        }
+
+        let mut n = self.suggestion.len();
+        if word {
+            let rest = self.suggestion.trim_start();
+            n -= rest.len() - rest.find(' ').unwrap_or(rest.len());
+        }
+
+        let accepted = Line::from(&self.suggestion[..n]);
+        for c in accepted.chars() {
+            if self.editor.is_full() {
+                break;
+            }
+            self.editor.insert_char(c);
+        }
+        self.refresh_line();
+        true
+    }
+
+    /// Removes the suggestion from the screen, e.g. before the line is left
+    pub(crate) fn hide_suggestion(&mut self) {
+        if !self.suggestion.is_empty() {
+            self.suggestion.clear();
+            self.draw_line();
+        }
+    }
+}
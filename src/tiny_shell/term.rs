@@ -0,0 +1,80 @@
+use core::fmt;
+
+/// Byte-level console the shell reads keys from and renders to.
+///
+/// The shell only emits plain text plus a handful of vt100 escape sequences, so a
+/// UART driver on a microcontroller and a raw-mode tty on the host both fit.
+pub trait Terminal {
+    /// Blocks until the next input byte arrives, `None` once the input is closed
+    fn read_byte(&mut self) -> Option<u8>;
+
+    /// Waits at most `timeout_ms` for the next input byte, `None` on timeout.
+    ///
+    /// Used to tell a bare Escape from the start of an escape sequence, backends
+    /// without a timer may keep the default which simply blocks.
+    fn read_byte_timeout(&mut self, timeout_ms: u32) -> Option<u8> {
+        let _ = timeout_ms;
+        self.read_byte()
+    }
+
+    /// Returns an input byte only if one is waiting already, never blocks.
+    ///
+    /// Lets the shell notice Ctrl+C while a command runs, the default reports no input.
+    fn poll_byte(&mut self) -> Option<u8> {
+        None
+    }
+
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Writes an error message, the default shares the output with everything else
+    fn write_error(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+
+    /// Line ending of the output, `\n\r` as the line editor needs it on a screen in raw
+    /// mode, backends whose output is read by other programs may return a plain `\n`
+    fn newline(&mut self) -> &'static [u8] {
+        b"\n\r"
+    }
+
+    /// Width of the screen in columns, `None` if unknown.
+    ///
+    /// Lets the line editor wrap long lines. A change of size is reported in the input
+    /// as a telnet NAWS sequence, which a remote session sends by itself.
+    fn columns(&mut self) -> Option<usize> {
+        None
+    }
+
+    /// Height of the screen in rows, `None` if unknown, needed for the status line
+    fn rows(&mut self) -> Option<usize> {
+        None
+    }
+
+    fn flush(&mut self) {}
+
+    /// Prepares the terminal for the line editor, e.g. by entering raw mode
+    fn enable_raw_mode(&mut self) {}
+
+    /// Restores the settings changed by `enable_raw_mode`
+    fn disable_raw_mode(&mut self) {}
+
+    /// Hands the terminal over to another program, e.g. by leaving raw mode
+    fn suspend(&mut self) {}
+
+    /// Takes the terminal back after `suspend`
+    fn resume(&mut self) {}
+
+    /* allows `write!(term, ...)` without an intermediate string */
+    fn write_fmt(&mut self, args: fmt::Arguments) {
+        let _ = fmt::write(&mut TermWriter(self), args);
+    }
+}
+
+struct TermWriter<'t, T: Terminal + ?Sized>(&'t mut T);
+
+impl<T: Terminal + ?Sized> fmt::Write for TermWriter<'_, T> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
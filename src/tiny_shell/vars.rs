@@ -0,0 +1,58 @@
+use super::{Line, List, ALIASES_MAX, VARS_MAX};
+
+/// Table of named values, backs the shell variables and the aliases
+pub struct Table<const N: usize> {
+    entries: List<(Line, Line), N>,
+}
+
+impl<const N: usize> Table<N> {
+    pub fn new() -> Table<N> {
+        Table {
+            entries: List::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| &key[..] == name)
+            .map(|(_, value)| &value[..])
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self.entries.iter_mut().find(|(key, _)| &key[..] == name) {
+            Some(entry) => entry.1 = Line::from(value),
+            None => self.entries.push((Line::from(name), Line::from(value))),
+        }
+    }
+
+    /// Removes the entry, returns false if it was not set
+    pub fn unset(&mut self, name: &str) -> bool {
+        match self.entries.iter().position(|(key, _)| &key[..] == name) {
+            Some(n) => {
+                self.entries.remove(n);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterates over `(name, value)` in the order the entries were first set
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (&key[..], &value[..]))
+    }
+}
+
+impl<const N: usize> Default for Table<N> {
+    fn default() -> Table<N> {
+        Table::new()
+    }
+}
+
+/// Variables set with the `set` builtin
+pub type Vars = Table<VARS_MAX>;
+
+/// Aliases defined with the `alias` builtin
+pub type Aliases = Table<ALIASES_MAX>;
@@ -0,0 +1,373 @@
+use super::keys::{self, Key, KeyCode, Modifiers};
+use super::term::Terminal;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+const CSI_PARAMS_MAX: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ParseState {
+    Ground,
+    Escape,
+    Csi,
+}
+
+/// In-memory terminal for exercising the shell without a tty.
+///
+/// Input is a scripted queue of bytes (or keys), output is interpreted as the subset of
+/// vt100 the shell emits and rendered into a grid of characters with a cursor. Reading
+/// from an exhausted script reports the input as closed, so `listen` returns instead of
+/// blocking and the screen can be inspected mid-edit.
+pub struct VirtualTerminal {
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+    width: usize,
+    height: usize,
+    grid: Vec<Vec<char>>,
+    cursor_row: usize,
+    cursor_col: usize,
+    /* rows the text scrolls within, set by DECSTBM */
+    scroll_top: usize,
+    scroll_bottom: usize,
+    saved_cursor: (usize, usize),
+    autowrap: bool,
+    state: ParseState,
+    params: [usize; CSI_PARAMS_MAX],
+    param_cnt: usize,
+    private: bool,
+    utf8: Vec<u8>,
+}
+
+impl VirtualTerminal {
+    pub fn new(width: usize, height: usize) -> VirtualTerminal {
+        VirtualTerminal {
+            input: VecDeque::new(),
+            output: Vec::new(),
+            width,
+            height,
+            grid: vec![vec![' '; width]; height],
+            cursor_row: 0,
+            cursor_col: 0,
+            scroll_top: 0,
+            scroll_bottom: height - 1,
+            saved_cursor: (0, 0),
+            autowrap: true,
+            state: ParseState::Ground,
+            params: [0; CSI_PARAMS_MAX],
+            param_cnt: 0,
+            private: false,
+            utf8: Vec::new(),
+        }
+    }
+
+    /// Queues raw input bytes, e.g. `"echo hi\r"`
+    pub fn feed_str(&mut self, s: &str) {
+        self.input.extend(s.as_bytes());
+    }
+
+    /// Queues a key press encoded the way an xterm sends it
+    pub fn feed_key(&mut self, key: Key) {
+        let mut bytes = Vec::new();
+        encode_key(key, &mut bytes);
+        self.input.extend(bytes);
+    }
+
+    pub fn feed_keys(&mut self, keys: &[Key]) {
+        for key in keys {
+            self.feed_key(*key);
+        }
+    }
+
+    /// Number of scripted input bytes not consumed yet
+    pub fn pending_input(&self) -> usize {
+        self.input.len()
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Changes the screen size like a window resize and reports it in the input.
+    ///
+    /// The text is cropped or padded, not reflowed, as an xterm does.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        for row in self.grid.iter_mut() {
+            row.resize(width, ' ');
+        }
+        self.grid.resize(height, vec![' '; width]);
+        self.width = width;
+        self.height = height;
+        self.scroll_top = 0;
+        self.scroll_bottom = height - 1;
+        self.cursor_row = self.cursor_row.min(height - 1);
+        self.cursor_col = self.cursor_col.min(width - 1);
+
+        self.feed_key(Key::new(KeyCode::Resize(width as u16, height as u16)));
+    }
+
+    /// Text of the given screen row with the trailing blanks removed
+    pub fn line(&self, row: usize) -> String {
+        let line: String = self.grid[row].iter().collect();
+        String::from(line.trim_end())
+    }
+
+    /// Text of the row holding the cursor
+    pub fn cursor_line(&self) -> String {
+        self.line(self.cursor_row)
+    }
+
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.height).map(|row| self.line(row)).collect()
+    }
+
+    /// Cursor position as `(row, col)`, both counted from zero
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor_row, self.cursor_col)
+    }
+
+    /// Every byte written so far, escape sequences included
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn clear_output(&mut self) {
+        self.output.clear();
+    }
+
+    fn put_char(&mut self, c: char) {
+        if self.cursor_col >= self.width && !self.autowrap {
+            /* without auto wrap the last column is overwritten */
+            self.cursor_col = self.width - 1;
+        } else if self.cursor_col >= self.width {
+            /* auto wrap to the next line */
+            self.cursor_col = 0;
+            self.line_feed();
+        }
+
+        self.grid[self.cursor_row][self.cursor_col] = c;
+        self.cursor_col += 1;
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor_row == self.scroll_bottom {
+            self.grid.remove(self.scroll_top);
+            self.grid.insert(self.scroll_bottom, vec![' '; self.width]);
+        } else if self.cursor_row + 1 < self.height {
+            self.cursor_row += 1;
+        }
+    }
+
+    fn param(&self, n: usize, default: usize) -> usize {
+        match self.params[n] {
+            0 => default,
+            p => p,
+        }
+    }
+
+    fn erase(&mut self, row: usize, from: usize, to: usize) {
+        for c in self.grid[row][from.min(self.width)..to.min(self.width)].iter_mut() {
+            *c = ' ';
+        }
+    }
+
+    fn csi(&mut self, final_byte: u8) {
+        let n = self.param(0, 1);
+
+        match final_byte {
+            b'A' => self.cursor_row = self.cursor_row.saturating_sub(n),
+            b'B' => self.cursor_row = (self.cursor_row + n).min(self.height - 1),
+            b'C' => self.cursor_col = (self.cursor_col + n).min(self.width - 1),
+            b'D' => self.cursor_col = self.cursor_col.min(self.width - 1).saturating_sub(n),
+            b'G' => self.cursor_col = (n - 1).min(self.width - 1),
+            b'H' | b'f' => {
+                self.cursor_row = (self.param(0, 1) - 1).min(self.height - 1);
+                self.cursor_col = (self.param(1, 1) - 1).min(self.width - 1);
+            }
+            b'r' => {
+                self.scroll_top = (self.param(0, 1) - 1).min(self.height - 1);
+                self.scroll_bottom =
+                    (self.param(1, self.height) - 1).clamp(self.scroll_top, self.height - 1);
+                self.cursor_row = 0;
+                self.cursor_col = 0;
+            }
+            b'h' | b'l' if self.private && self.params[0] == 7 => {
+                self.autowrap = final_byte == b'h'
+            }
+            b'K' => {
+                let row = self.cursor_row;
+                match self.params[0] {
+                    0 => self.erase(row, self.cursor_col, self.width),
+                    1 => self.erase(row, 0, self.cursor_col + 1),
+                    _ => self.erase(row, 0, self.width),
+                }
+            }
+            b'J' => {
+                let row = self.cursor_row;
+                match self.params[0] {
+                    0 => {
+                        self.erase(row, self.cursor_col, self.width);
+                        for r in (row + 1)..self.height {
+                            self.erase(r, 0, self.width);
+                        }
+                    }
+                    1 => {
+                        for r in 0..row {
+                            self.erase(r, 0, self.width);
+                        }
+                        self.erase(row, 0, self.cursor_col + 1);
+                    }
+                    _ => {
+                        for r in 0..self.height {
+                            self.erase(r, 0, self.width);
+                        }
+                    }
+                }
+            }
+            _ => {} //colors and modes do not affect the text grid
+        }
+    }
+
+    fn process(&mut self, c: u8) {
+        match self.state {
+            ParseState::Ground => match c {
+                0x1b => self.state = ParseState::Escape,
+                b'\r' => self.cursor_col = 0,
+                b'\n' => self.line_feed(),
+                0x08 => self.cursor_col = self.cursor_col.saturating_sub(1),
+                0x00..=0x1f | 0x7f => {}
+                0x20..=0x7e => self.put_char(c as char),
+                _ => {
+                    /* collect the utf-8 sequence before drawing it */
+                    self.utf8.push(c);
+                    if let Ok(s) = core::str::from_utf8(&self.utf8) {
+                        let ch = s.chars().next().unwrap_or(' ');
+                        self.utf8.clear();
+                        self.put_char(ch);
+                    } else if self.utf8.len() >= 4 {
+                        self.utf8.clear();
+                    }
+                }
+            },
+            ParseState::Escape => {
+                self.state = ParseState::Ground;
+                match c {
+                    b'[' => {
+                        self.params = [0; CSI_PARAMS_MAX];
+                        self.param_cnt = 0;
+                        self.private = false;
+                        self.state = ParseState::Csi;
+                    }
+                    b'7' => self.saved_cursor = (self.cursor_row, self.cursor_col),
+                    b'8' => (self.cursor_row, self.cursor_col) = self.saved_cursor,
+                    _ => {}
+                }
+            }
+            ParseState::Csi => match c {
+                b'0'..=b'9' => {
+                    if self.param_cnt < CSI_PARAMS_MAX {
+                        let p = &mut self.params[self.param_cnt];
+                        *p = *p * 10 + (c - b'0') as usize;
+                    }
+                }
+                b';' => self.param_cnt += 1,
+                b'?' => self.private = true,
+                0x20..=0x3f => {}
+                _ => {
+                    self.state = ParseState::Ground;
+                    self.csi(c);
+                }
+            },
+        }
+    }
+}
+
+impl Terminal for VirtualTerminal {
+    fn read_byte(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn read_byte_timeout(&mut self, _timeout_ms: u32) -> Option<u8> {
+        /* the script never pauses mid-sequence, running dry counts as the timeout */
+        self.input.pop_front()
+    }
+
+    fn poll_byte(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.output.extend_from_slice(bytes);
+        for c in bytes {
+            self.process(*c);
+        }
+    }
+
+    fn columns(&mut self) -> Option<usize> {
+        Some(self.width)
+    }
+
+    fn rows(&mut self) -> Option<usize> {
+        Some(self.height)
+    }
+}
+
+/// Appends the bytes an xterm sends for the key
+fn encode_key(key: Key, bytes: &mut Vec<u8>) {
+    let mods = key.mods;
+    let xterm_mod = 1
+        + (mods.contains(Modifiers::SHIFT) as u8)
+        + 2 * (mods.contains(Modifiers::ALT) as u8)
+        + 4 * (mods.contains(Modifiers::CTRL) as u8);
+
+    let csi = |bytes: &mut Vec<u8>, param: Option<u8>, final_byte: u8| {
+        let seq = match (param, xterm_mod) {
+            (Some(p), 1) => format!("\x1b[{}{}", p, final_byte as char),
+            (Some(p), m) => format!("\x1b[{};{}{}", p, m, final_byte as char),
+            (None, 1) => format!("\x1b[{}", final_byte as char),
+            (None, m) => format!("\x1b[1;{}{}", m, final_byte as char),
+        };
+        bytes.extend_from_slice(seq.as_bytes());
+    };
+
+    match key.code {
+        KeyCode::Char(c) => {
+            if mods.contains(Modifiers::ALT) {
+                bytes.push(0x1b);
+            }
+            if mods.contains(Modifiers::CTRL) {
+                bytes.push(match c {
+                    'a'..='z' => c as u8 - b'a' + 1,
+                    '\\'..='_' => c as u8 - b'\\' + 28,
+                    _ => 0,
+                });
+            } else {
+                let mut utf8 = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+            }
+        }
+        KeyCode::Enter => bytes.push(b'\r'),
+        KeyCode::Tab if mods.contains(Modifiers::SHIFT) => bytes.extend_from_slice(b"\x1b[Z"),
+        KeyCode::Tab => bytes.push(b'\t'),
+        KeyCode::Backspace => bytes.push(0x7f),
+        KeyCode::Escape => bytes.push(0x1b),
+        KeyCode::Up => csi(bytes, None, b'A'),
+        KeyCode::Down => csi(bytes, None, b'B'),
+        KeyCode::Right => csi(bytes, None, b'C'),
+        KeyCode::Left => csi(bytes, None, b'D'),
+        KeyCode::Home => csi(bytes, None, b'H'),
+        KeyCode::End => csi(bytes, None, b'F'),
+        KeyCode::Insert => csi(bytes, Some(2), b'~'),
+        KeyCode::Delete => csi(bytes, Some(3), b'~'),
+        KeyCode::PageUp => csi(bytes, Some(5), b'~'),
+        KeyCode::PageDown => csi(bytes, Some(6), b'~'),
+        KeyCode::F(n @ 1..=4) => csi(bytes, None, b'P' + n - 1),
+        KeyCode::F(n @ 5) => csi(bytes, Some(n + 10), b'~'),
+        KeyCode::F(n @ 6..=10) => csi(bytes, Some(n + 11), b'~'),
+        KeyCode::F(n @ 11..=12) => csi(bytes, Some(n + 12), b'~'),
+        KeyCode::Resize(cols, rows) => bytes.extend(keys::naws(cols, rows)),
+        KeyCode::F(_) | KeyCode::Unknown => {}
+    }
+}
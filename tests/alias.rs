@@ -0,0 +1,99 @@
+mod common;
+
+use rust_shell::tiny_shell::{Input, Io, Key, KeyCode, Shell, VirtualTerminal, STATUS_UNKNOWN_CMD};
+use std::cell::RefCell;
+
+thread_local! {
+    static ARGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn recorded() -> Vec<String> {
+    ARGS.with(|args| args.borrow().clone())
+}
+
+fn cmd_record(argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    ARGS.with(|args| *args.borrow_mut() = argc.iter().map(|arg| arg.to_string()).collect());
+    0
+}
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(60, 10)
+        .command("record", cmd_record)
+        .command("reset", cmd_record)
+        .build()
+}
+
+#[test]
+fn appends_arguments_without_positionals() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("alias rec='record -v'"), 0);
+    shell.parse("rec 'a b' c");
+    assert_eq!(recorded(), ["record", "-v", "a b", "c"]);
+}
+
+#[test]
+fn positional_substitution() {
+    let mut shell = shell();
+    shell.parse("alias swap='record $2 \"<$1>\" $# $0'");
+    shell.parse("swap \"it's\" \"x \\$y\"");
+    assert_eq!(recorded(), ["record", "x $y", "<it's>", "2", "swap"]);
+
+    shell.parse("alias all='record first $@ last'");
+    shell.parse("all a 'b c'");
+    assert_eq!(recorded(), ["record", "first", "a", "b c", "last"]);
+}
+
+#[test]
+fn recursion_protection() {
+    let mut shell = shell();
+
+    /* an alias may wrap the command it is named after */
+    shell.parse("alias record='record --wrapped'");
+    shell.parse("record x");
+    assert_eq!(recorded(), ["record", "--wrapped", "x"]);
+
+    shell.parse("alias ping=pong");
+    shell.parse("alias pong=ping");
+    assert_eq!(shell.parse("ping"), STATUS_UNKNOWN_CMD);
+}
+
+#[test]
+fn alias_listing_and_unalias() {
+    let mut shell = shell();
+    shell.parse("alias ll='record -l' la='record -a'");
+    shell.parse("alias");
+    assert_eq!(shell.terminal().line(0), "ll='record -l'");
+    assert_eq!(shell.terminal().line(1), "la='record -a'");
+
+    assert_eq!(shell.parse("unalias ll"), 0);
+    assert_eq!(shell.parse("ll"), STATUS_UNKNOWN_CMD);
+    assert_eq!(shell.parse("unalias ll"), 1);
+    assert_eq!(shell.parse("alias bad\\ name=x"), 1);
+
+    shell.parse("help");
+    let screen = shell.terminal().screen().join("\n");
+    assert!(screen.contains("record\nreset\nla='record -a'"));
+}
+
+#[test]
+fn completion_includes_aliases() {
+    let mut shell = shell();
+    shell.parse("alias rebuild='record build'");
+
+    shell.terminal().feed_str("reb\t");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > rebuild");
+
+    shell.terminal().feed_key(Key::ctrl('u'));
+    shell.terminal().feed_str("re\t");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > re");
+    let row = shell.terminal().cursor().0;
+    assert_eq!(shell.terminal().line(row - 1), "record  reset  rebuild");
+
+    shell
+        .terminal()
+        .feed_keys(&[Key::new(KeyCode::Char('c')), Key::new(KeyCode::Tab)]);
+    shell.terminal().feed_str("x\r");
+    assert_eq!(shell.listen().as_line(), Some("record x"));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use rust_shell::tiny_shell::{split_chain, ChainOp, Io, ParseError, Shell, VirtualTerminal};
+use std::cell::RefCell;
+
+thread_local! {
+    static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn log() -> Vec<String> {
+    LOG.with(|log| log.borrow_mut().drain(..).collect())
+}
+
+fn cmd_log(argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    LOG.with(|log| log.borrow_mut().push(argc[1..].join(" ")));
+    0
+}
+
+fn cmd_false(_argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    1
+}
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(60, 10)
+        .command("log", cmd_log)
+        .command("false", cmd_false)
+        .build()
+}
+
+fn split(line: &str) -> Result<Vec<(Option<ChainOp>, &str)>, ParseError> {
+    split_chain(line).collect()
+}
+
+#[test]
+fn splits_outside_of_quotes() {
+    assert_eq!(
+        split("a 'x;y' && b \"||\" || c\\;d ; e # f && g").unwrap(),
+        [
+            (None, "a 'x;y' "),
+            (Some(ChainOp::And), " b \"||\" "),
+            (Some(ChainOp::Or), " c\\;d "),
+            (Some(ChainOp::Seq), " e "),
+        ]
+    );
+    assert_eq!(split("a;").unwrap(), [(None, "a")]);
+    assert_eq!(split("").unwrap(), []);
+}
+
+#[test]
+fn syntax_errors() {
+    assert_eq!(
+        split("&& a"),
+        Err(ParseError::UnexpectedOperator(ChainOp::And))
+    );
+    assert_eq!(
+        split("a ;; b"),
+        Err(ParseError::UnexpectedOperator(ChainOp::Seq))
+    );
+    assert_eq!(split("a ||"), Err(ParseError::UnexpectedEnd));
+
+    /* nothing runs when the line does not parse */
+    let mut shell = shell();
+    assert_eq!(shell.parse("log a ; || log b"), 2);
+    assert!(log().is_empty());
+    assert_eq!(shell.terminal().line(0), "syntax error near `||`.");
+}
+
+#[test]
+fn conditional_execution() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("false && log a || log b ; log c"), 0);
+    assert_eq!(log(), ["b", "c"]);
+
+    assert_eq!(shell.parse("log a && false || false"), 1);
+    assert_eq!(log(), ["a"]);
+
+    assert_eq!(shell.parse("false ; log $? && log $?"), 0);
+    assert_eq!(log(), ["1", "0"]);
+}
+
+#[test]
+fn alias_body_with_chain() {
+    let mut shell = shell();
+    shell.parse("alias both='log $1 && log $2'");
+    assert_eq!(shell.parse("both x y || log z"), 0);
+    assert_eq!(log(), ["x", "y"]);
+}
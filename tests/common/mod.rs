@@ -0,0 +1,71 @@
+//! Helpers shared by the integration tests, every test file uses only some of them
+
+#![allow(dead_code)]
+
+use rust_shell::tiny_shell::{CommandFn, Io, Shell, VirtualTerminal};
+use std::cell::RefCell;
+
+/// Sets up a shell on a virtual terminal, e.g. `shell(60, 10).command("echo", cmd_echo).build()`
+pub struct ShellBuilder {
+    shell: Shell<'static, VirtualTerminal>,
+}
+
+/// Starts a shell with the `shell > ` prompt on a screen of `width` × `height` characters
+pub fn shell(width: usize, height: usize) -> ShellBuilder {
+    ShellBuilder {
+        shell: Shell::new("shell > ", VirtualTerminal::new(width, height)),
+    }
+}
+
+impl ShellBuilder {
+    pub fn command(mut self, name: &'static str, func: CommandFn) -> ShellBuilder {
+        self.shell.add_command(name, func);
+        self
+    }
+
+    /// Adds the text filters `grep`, `head`, `tail`, `wc` and `sort`
+    pub fn filters(mut self) -> ShellBuilder {
+        self.shell.add_filters();
+        self
+    }
+
+    /// Runs programs from `PATH`
+    #[cfg(feature = "std")]
+    pub fn external(mut self) -> ShellBuilder {
+        self.shell.enable_external_commands(true);
+        self
+    }
+
+    pub fn build(self) -> Shell<'static, VirtualTerminal> {
+        self.shell
+    }
+}
+
+thread_local! {
+    static INPUT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Prints its arguments on one line
+pub fn cmd_echo(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    writeln!(io, "{}", argc[1..].join(" "));
+    0
+}
+
+/// Prints every argument on a line of its own
+pub fn cmd_lines(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    for arg in &argc[1..] {
+        writeln!(io, "{}", arg);
+    }
+    0
+}
+
+/// Keeps the piped input for `captured`
+pub fn cmd_capture(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    INPUT.with(|input| *input.borrow_mut() = io.input().map(String::from));
+    0
+}
+
+/// Input `cmd_capture` was given last, `None` if it ran without piped input
+pub fn captured() -> Option<String> {
+    INPUT.with(|input| input.borrow_mut().take())
+}
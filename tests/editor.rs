@@ -0,0 +1,266 @@
+mod common;
+
+use rust_shell::tiny_shell::{Input, Key, KeyCode};
+
+const LEFT: Key = Key::new(KeyCode::Left);
+const UP: Key = Key::new(KeyCode::Up);
+const DOWN: Key = Key::new(KeyCode::Down);
+
+#[test]
+fn typing_and_editing_updates_screen() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("ech");
+    shell
+        .terminal()
+        .feed_keys(&[LEFT, Key::new(KeyCode::Backspace)]);
+
+    /* the script runs dry before enter is pressed */
+    assert_eq!(shell.listen(), Input::Eof);
+
+    let term = shell.terminal();
+    assert_eq!(term.line(0), "shell > eh");
+    assert_eq!(term.cursor(), (0, 9));
+}
+
+#[test]
+fn delete_and_line_kill() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("abc");
+    shell
+        .terminal()
+        .feed_keys(&[Key::new(KeyCode::Home), Key::new(KeyCode::Delete)]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "shell > bc");
+    assert_eq!(shell.terminal().cursor(), (0, 8));
+
+    shell.terminal().feed_key(Key::ctrl('u'));
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "shell >");
+}
+
+#[test]
+fn ctrl_d_deletes_or_ends_the_input() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("abc");
+    shell
+        .terminal()
+        .feed_keys(&[LEFT, LEFT, Key::ctrl('d'), Key::ctrl('u'), Key::ctrl('d')]);
+    shell.terminal().feed_str("x\r");
+
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "shell >");
+    assert_eq!(shell.terminal().cursor(), (1, 0));
+    assert_eq!(shell.terminal().pending_input(), 2);
+}
+
+#[test]
+fn run_returns_the_exit_status() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("exit x\rexit 3; exit 4\rset\r");
+
+    assert_eq!(shell.run(), 3);
+    assert_eq!(shell.terminal().line(1), "exit: numeric argument required.");
+
+    /* the end of the input leaves with the status of the last command */
+    shell.terminal().feed_str("unset\r");
+    assert_eq!(shell.run(), 2);
+    assert!(shell
+        .terminal()
+        .screen()
+        .contains(&"shell > set".to_string()));
+}
+
+#[test]
+fn long_lines_wrap_and_reflow_on_resize() {
+    let mut shell = common::shell(20, 10).build();
+    shell.terminal().feed_str("abcdefghijklmno");
+    shell.terminal().feed_key(Key::new(KeyCode::Home));
+    shell.terminal().feed_str("X");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().screen()[..2],
+        ["shell > Xabcdefghijk", "lmno"]
+    );
+    assert_eq!(shell.terminal().cursor(), (0, 9));
+
+    shell.terminal().resize(15, 10);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().screen()[..3],
+        ["shell > Xabcdef", "ghijklmno", ""]
+    );
+    assert_eq!(shell.terminal().cursor(), (0, 9));
+
+    /* a line filling the row exactly ends on the next one */
+    shell
+        .terminal()
+        .feed_keys(&[Key::new(KeyCode::End), Key::ctrl('u')]);
+    shell.terminal().feed_str("1234567\r");
+    assert_eq!(shell.listen().as_line(), Some("1234567"));
+    assert_eq!(shell.terminal().screen()[..2], ["shell > 1234567", ""]);
+    assert_eq!(shell.terminal().cursor(), (1, 0));
+}
+
+#[test]
+fn enter_returns_line_and_moves_down() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("echo hi");
+    shell.terminal().feed_keys(&[LEFT, LEFT]);
+    shell.terminal().feed_str("\r");
+
+    assert_eq!(shell.listen().as_line(), Some("echo hi"));
+    assert_eq!(shell.terminal().line(0), "shell > echo hi");
+    assert_eq!(shell.terminal().cursor(), (1, 0));
+}
+
+#[test]
+fn history_navigation_restores_typing() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("this\rthat\r");
+    assert_eq!(shell.listen().as_line(), Some("this"));
+    assert_eq!(shell.listen().as_line(), Some("that"));
+
+    shell.terminal().feed_str("th");
+    shell.terminal().feed_keys(&[UP, UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > this");
+
+    shell.terminal().feed_keys(&[DOWN]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > that");
+
+    /* the typing comes back, with the latest match suggested behind it */
+    shell.terminal().feed_keys(&[DOWN]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > that");
+    assert_eq!(shell.terminal().cursor(), (2, 10));
+}
+
+#[test]
+fn history_navigation_filters_by_the_typed_prefix() {
+    let mut shell = common::shell(40, 10).build();
+    shell
+        .terminal()
+        .feed_str("git log\rls\rgit status\rgit status\rmake\r");
+    for _ in 0..5 {
+        assert!(shell.listen().as_line().is_some());
+    }
+
+    /* duplicates of the shown entry and entries not starting with "git" are skipped */
+    shell.terminal().feed_str("git");
+    shell.terminal().feed_keys(&[UP, UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > git log");
+
+    shell.terminal().feed_key(Key::new(KeyCode::PageDown));
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > git status");
+
+    /* past the oldest match the typing comes back */
+    shell.terminal().feed_keys(&[UP, UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor(), (5, 11));
+
+    /* without a prefix every entry is visited */
+    shell.terminal().feed_str("\x15");
+    shell.terminal().feed_keys(&[UP, UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > git status");
+}
+
+#[test]
+fn suggestions_from_history() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("echo hello world\rls\r");
+    assert_eq!(shell.listen().as_line(), Some("echo hello world"));
+    assert_eq!(shell.listen().as_line(), Some("ls"));
+
+    /* the rest of the latest matching entry is shown dimmed behind the cursor */
+    shell.terminal().clear_output();
+    shell.terminal().feed_str("ec");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > echo hello world");
+    assert_eq!(shell.terminal().cursor(), (2, 10));
+    assert!(shell
+        .terminal()
+        .output()
+        .ends_with(b"ec\x1b[2mho hello world\x1b[0m\x1b[14D"));
+
+    /* Alt+F takes one word, Right Arrow the rest */
+    shell.terminal().feed_keys(&[Key::alt('f'), Key::alt('f')]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor(), (2, 18));
+
+    shell.terminal().feed_key(Key::new(KeyCode::Right));
+    shell.terminal().feed_str("\r");
+    assert_eq!(shell.listen().as_line(), Some("echo hello world"));
+}
+
+#[test]
+fn suggestion_is_hidden_when_leaving_the_line() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("echo hello\rec\x03e\r");
+    assert_eq!(shell.listen().as_line(), Some("echo hello"));
+    assert_eq!(shell.listen(), Input::Interrupted);
+    assert_eq!(shell.listen().as_line(), Some("e"));
+
+    assert_eq!(
+        shell.terminal().screen()[..4],
+        ["shell > echo hello", "shell > ec^C", "shell > e", ""]
+    );
+}
+
+#[test]
+fn unknown_escape_sequences_are_swallowed() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("a\x1b[99Xb\x1bOHc");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "shell > cab");
+    assert_eq!(shell.terminal().cursor(), (0, 9));
+}
+
+#[test]
+fn open_quotes_and_trailing_backslashes_continue_the_line() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("echo 'a\rb' \\\r| wc\r");
+
+    assert_eq!(shell.listen().as_line(), Some("echo 'a\nb' \\\n| wc"));
+    assert_eq!(
+        shell.terminal().screen()[..4],
+        ["shell > echo 'a", "> b' \\", "> | wc", ""]
+    );
+    assert_eq!(shell.history().get(0), Some("echo 'a\nb' \\\n| wc"));
+}
+
+#[test]
+fn arrows_move_between_the_lines_of_the_input() {
+    let mut shell = common::shell(40, 10).build();
+    shell.terminal().feed_str("earlier\r");
+    assert_eq!(shell.listen().as_line(), Some("earlier"));
+
+    shell.terminal().feed_str("echo \"one\rtwo");
+    shell.terminal().feed_keys(&[UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor(), (1, 11));
+
+    shell.terminal().feed_str("!");
+    shell.terminal().feed_keys(&[DOWN]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().screen()[1..3],
+        ["shell > ech!o \"one", "> two"]
+    );
+    assert_eq!(shell.terminal().cursor(), (2, 5));
+
+    shell.terminal().feed_str("\"\r");
+    assert_eq!(shell.listen().as_line(), Some("ech!o \"one\ntwo\""));
+
+    /* from the first line the arrows step through the history again */
+    shell.terminal().feed_keys(&[UP, UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor(), (3, 12));
+
+    shell.terminal().feed_keys(&[UP]);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().screen()[3..5], ["shell > earlier", ""]);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::{captured, cmd_capture, cmd_lines};
+use rust_shell::tiny_shell::{find_in_path, Shell, VirtualTerminal, STATUS_UNKNOWN_CMD};
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(60, 10)
+        .command("lines", cmd_lines)
+        .command("capture", cmd_capture)
+        .external()
+        .build()
+}
+
+#[test]
+fn resolves_on_path() {
+    assert!(find_in_path("sh").is_some());
+    assert!(find_in_path("/bin/sh").is_some());
+    assert!(find_in_path("no-such-program-here").is_none());
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut shell = common::shell(60, 10).build();
+    assert_eq!(shell.parse("sh -c true"), STATUS_UNKNOWN_CMD);
+    assert_eq!(shell.terminal().line(0), "unknown command.");
+}
+
+#[test]
+fn reports_exit_status() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("sh -c 'exit 3'"), 3);
+    assert_eq!(shell.parse("sh -c 'kill -9 $$'"), 128 + 9);
+    assert_eq!(shell.parse("no-such-program-here"), STATUS_UNKNOWN_CMD);
+}
+
+#[test]
+fn pipes_through_programs() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("lines b a | tr ab xy | capture"), 0);
+    let output = captured();
+    assert_eq!(output.as_deref(), Some("y\nx\n"));
+}
+
+#[test]
+fn highlighting_looks_a_program_up_once_per_line() {
+    use rust_shell::tiny_shell::{highlight, Input, Key, KeyCode};
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("rust_shell_cache_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prog = dir.join("prog");
+    std::fs::write(&prog, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&prog, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut shell = common::shell(60, 10).external().build();
+    shell.set_highlighter(Some(highlight));
+    let green = format!("\x1b[32m{}\x1b[0m", prog.display());
+    let red = format!("\x1b[31m{}\x1b[0m", prog.display());
+    let output = |shell: &mut Shell<VirtualTerminal>| {
+        let output = String::from_utf8_lossy(shell.terminal().output()).into_owned();
+        shell.terminal().clear_output();
+        output
+    };
+
+    shell.terminal().feed_str(&prog.display().to_string());
+    assert_eq!(shell.listen(), Input::Eof);
+    assert!(output(&mut shell).ends_with(&green));
+
+    /* the line is redrawn from what was found before */
+    std::fs::remove_dir_all(&dir).unwrap();
+    shell.terminal().feed_key(Key::new(KeyCode::Left));
+    assert_eq!(shell.listen(), Input::Eof);
+    assert!(output(&mut shell).contains(&green));
+
+    /* the next line looks again */
+    shell.terminal().feed_key(Key::ctrl('c'));
+    assert_eq!(shell.listen(), Input::Interrupted);
+    shell.terminal().feed_str(&prog.display().to_string());
+    assert_eq!(shell.listen(), Input::Eof);
+    assert!(output(&mut shell).ends_with(&red));
+}
@@ -0,0 +1,41 @@
+//! The fixed-capacity shell is stored whole in RAM, these budgets catch a capacity or a
+//! field growing it unnoticed
+
+#![cfg(not(feature = "alloc"))]
+
+use rust_shell::tiny_shell::{Aliases, History, LineEditor, Shell, Terminal, Vars, CMD_LEN_MAX};
+use std::mem::size_of;
+
+struct Uart;
+
+impl Terminal for Uart {
+    fn read_byte(&mut self) -> Option<u8> {
+        None
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+#[test]
+fn shell_fits_a_microcontroller() {
+    assert!(size_of::<History>() <= 1024, "{}", size_of::<History>());
+    assert!(size_of::<Vars>() <= 1280, "{}", size_of::<Vars>());
+    assert!(size_of::<Aliases>() <= 640, "{}", size_of::<Aliases>());
+    assert!(
+        size_of::<Shell<Uart>>() <= 6 * 1024,
+        "{}",
+        size_of::<Shell<Uart>>()
+    );
+}
+
+#[test]
+fn typed_line_always_fits_a_line() {
+    for c in ['a', 'ñ', '€', '🦀'] {
+        let mut editor = LineEditor::new();
+        for _ in 0..CMD_LEN_MAX {
+            editor.insert_char(c);
+        }
+        assert!(editor.is_full());
+        assert_eq!(editor.text().chars().count(), editor.char_cnt());
+    }
+}
@@ -0,0 +1,60 @@
+use rust_shell::tiny_shell::{highlight, Input, Io, Shell, Style, VirtualTerminal};
+
+fn cmd_ok(_argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    0
+}
+
+fn pieces(line: &str) -> Vec<(String, Style)> {
+    let mut pieces = Vec::new();
+    highlight(line, &|word| word == "ok", &mut |piece, style| {
+        pieces.push((piece.to_string(), style))
+    });
+    pieces
+}
+
+fn styled(line: &str, style: Style) -> Vec<String> {
+    pieces(line)
+        .into_iter()
+        .filter(|(_, s)| *s == style)
+        .map(|(piece, _)| piece)
+        .collect()
+}
+
+#[test]
+fn splits_the_line_into_styled_pieces() {
+    let line = "ok \"a b\" $X | nope >out; ok 'x' # done";
+    assert_eq!(
+        pieces(line)
+            .iter()
+            .map(|(p, _)| p.as_str())
+            .collect::<String>(),
+        line
+    );
+    assert_eq!(styled(line, Style::Command), ["ok", "ok"]);
+    assert_eq!(styled(line, Style::UnknownCommand), ["nope"]);
+    assert_eq!(styled(line, Style::Quoted), ["\"a b\"", "'x'"]);
+    assert_eq!(styled(line, Style::Variable), ["$X"]);
+    assert_eq!(styled(line, Style::Operator), ["|", ">", ";"]);
+    assert_eq!(styled(line, Style::Comment), ["# done"]);
+}
+
+#[test]
+fn unclosed_quotes_are_errors() {
+    assert_eq!(styled("ok a\"b c", Style::Error), ["\"b c"]);
+    assert_eq!(styled("ok ${X", Style::Error), ["${X"]);
+    assert_eq!(styled("ok 'a\\'", Style::Error), Vec::<String>::new());
+}
+
+#[test]
+fn colors_keep_the_cursor_in_place() {
+    let mut shell = Shell::new("> ", VirtualTerminal::new(10, 5));
+    shell.add_command("ok", cmd_ok);
+    shell.set_highlighter(Some(highlight));
+    shell.terminal().feed_str("ok $HOME\x1b[D\x1b[D");
+    assert_eq!(shell.listen(), Input::Eof);
+
+    let output = String::from_utf8_lossy(shell.terminal().output()).into_owned();
+    assert!(output.contains("\x1b[32mok\x1b[0m \x1b[36m$HOME\x1b[0m"));
+    assert_eq!(shell.terminal().screen()[..2], ["> ok $HOME", ""]);
+    assert_eq!(shell.terminal().cursor(), (0, 8));
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::cmd_echo;
+use rust_shell::tiny_shell::{History, HistoryOptions, Shell, VirtualTerminal};
+
+fn type_lines(shell: &mut Shell<'static, VirtualTerminal>, lines: &[&str]) {
+    for line in lines {
+        shell.terminal().feed_str(line);
+        shell.terminal().feed_str("\r");
+        assert!(shell.listen().as_line().is_some());
+    }
+}
+
+fn entries(shell: &Shell<'static, VirtualTerminal>) -> Vec<String> {
+    shell.history().iter().map(String::from).collect()
+}
+
+#[test]
+fn every_line_is_kept_by_default() {
+    let mut shell = common::shell(60, 10).build();
+    type_lines(&mut shell, &["a", "a", " b", "a"]);
+    assert_eq!(entries(&shell), ["a", "a", " b", "a"]);
+}
+
+#[test]
+fn options_skip_duplicates_and_hidden_lines() {
+    let mut shell = common::shell(60, 10).build();
+    shell.set_history_options(HistoryOptions {
+        ignore_dups: true,
+        ignore_space: true,
+        ..Default::default()
+    });
+    type_lines(&mut shell, &["a", "a", " secret", "b", "a"]);
+    assert_eq!(entries(&shell), ["a", "b", "a"]);
+
+    let mut shell = common::shell(60, 10).build();
+    shell.set_history_options(HistoryOptions {
+        erase_dups: true,
+        ..Default::default()
+    });
+    type_lines(&mut shell, &["a", "b", "a", "c", "b"]);
+    assert_eq!(entries(&shell), ["a", "c", "b"]);
+}
+
+#[test]
+fn entries_carry_timestamps() {
+    let mut history = History::new();
+    history.push_at("make", 1_700_000_000);
+    history.push("ls");
+
+    let entries: Vec<_> = history.iter_timed().collect();
+    assert_eq!(entries[0], ("make", 1_700_000_000));
+    assert_eq!(entries[1].0, "ls");
+    /* the time is only known with `std` */
+    #[cfg(feature = "std")]
+    assert!(entries[1].1 > 1_700_000_000);
+}
+
+#[test]
+fn history_builtin_lists_tails_and_clears() {
+    let mut shell = common::shell(60, 10).build();
+    type_lines(&mut shell, &["first", "second", "third"]);
+
+    assert_eq!(shell.parse("history 2"), 0);
+    let screen = shell.terminal().screen();
+    assert!(screen[3].starts_with("    2  ") && screen[3].ends_with("  second"));
+    assert!(screen[4].starts_with("    3  ") && screen[4].ends_with("  third"));
+    assert_eq!(screen[5], "");
+
+    assert_eq!(shell.parse("history x"), 2);
+    assert_eq!(
+        shell.terminal().line(5),
+        "history: numeric argument required."
+    );
+
+    assert_eq!(shell.parse("history -c"), 0);
+    assert!(shell.history().is_empty());
+}
+
+/* types the line and runs it, as `run` does */
+fn run(shell: &mut Shell<'static, VirtualTerminal>, line: &str) -> i32 {
+    shell.terminal().feed_str(line);
+    shell.terminal().feed_str("\r");
+    let cmd = shell.listen().as_line().unwrap().to_string();
+    shell.parse(&cmd)
+}
+
+#[test]
+fn history_references_expand_and_are_echoed() {
+    let mut shell = common::shell(60, 10).build();
+    shell.add_command("echo", cmd_echo);
+    run(&mut shell, "echo one two");
+    run(&mut shell, "make all");
+
+    shell.terminal().clear_output();
+    assert_eq!(run(&mut shell, "echo !-2 '!!'"), 0);
+    let output = String::from_utf8(shell.terminal().output().to_vec()).unwrap();
+    assert!(output.ends_with("echo echo one two '!!'\n\recho one two !!\n\r"));
+
+    let cases = [
+        ("echo !$", "echo '!!'"),
+        ("echo !1", "echo echo one two"),
+        ("echo !ma", "echo make all"),
+        ("^make^made", "echo made all"),
+    ];
+    for (line, expanded) in cases {
+        run(&mut shell, line);
+        assert_eq!(shell.history().get(0), Some(expanded));
+    }
+
+    /* the typed line is stored expanded */
+    run(&mut shell, "!!");
+    assert_eq!(shell.history().get(0), Some("echo made all"));
+    assert_eq!(shell.history().get(1), Some("echo made all"));
+}
+
+#[test]
+fn missing_events_fail_and_expansion_can_be_turned_off() {
+    let mut shell = common::shell(60, 10).build();
+    shell.add_command("echo", cmd_echo);
+    run(&mut shell, "echo a");
+
+    assert_eq!(run(&mut shell, "echo !nope"), 1);
+    assert_eq!(shell.terminal().line(3), "!nope: event not found.");
+    assert_eq!(run(&mut shell, "^x^y"), 1);
+    assert_eq!(shell.terminal().line(5), "^x^y: substitution failed.");
+
+    shell.set_history_expansion(false);
+    assert_eq!(run(&mut shell, "echo hi!!"), 0);
+    assert_eq!(shell.terminal().line(7), "hi!!");
+}
+
+#[test]
+fn failed_expansions_are_not_stored() {
+    let mut shell = common::shell(60, 10).build();
+    shell.add_command("echo", cmd_echo);
+    run(&mut shell, "echo a");
+
+    assert_eq!(run(&mut shell, "!nope"), 1);
+    assert_eq!(run(&mut shell, "^zz^q"), 1);
+    assert_eq!(entries(&shell), ["echo a"]);
+
+    /* `!!` still refers to the last command that ran */
+    assert_eq!(run(&mut shell, "!!"), 0);
+    assert_eq!(shell.terminal().line(7), "echo a");
+    assert_eq!(shell.terminal().line(8), "a");
+    assert_eq!(entries(&shell), ["echo a", "echo a"]);
+}
+
+#[test]
+fn bang_before_an_operator_names_no_event() {
+    let mut shell = common::shell(60, 10).build();
+    shell.add_command("echo", cmd_echo);
+    run(&mut shell, "echo one");
+
+    assert_eq!(run(&mut shell, "echo hi!; echo two"), 1);
+    assert_eq!(shell.terminal().line(3), "!: event not found.");
+    assert_eq!(run(&mut shell, "echo wow!|echo x"), 1);
+    assert_eq!(shell.terminal().line(5), "!: event not found.");
+}
+
+#[test]
+fn expanded_lines_are_stored_through_the_options() {
+    let mut shell = common::shell(60, 10).build();
+    shell.set_history_options(HistoryOptions {
+        ignore_dups: true,
+        ..Default::default()
+    });
+    run(&mut shell, "help");
+    run(&mut shell, "!!");
+    assert_eq!(entries(&shell), ["help"]);
+
+    let mut shell = common::shell(60, 10).build();
+    shell.set_history_options(HistoryOptions {
+        erase_dups: true,
+        ..Default::default()
+    });
+    run(&mut shell, "help");
+    run(&mut shell, "set A 1");
+    run(&mut shell, "!h");
+    assert_eq!(entries(&shell), ["set A 1", "help"]);
+}
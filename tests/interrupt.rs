@@ -0,0 +1,119 @@
+mod common;
+
+use rust_shell::tiny_shell::{Input, Io, Key, Shell, VirtualTerminal, STATUS_INTERRUPTED};
+use std::cell::Cell;
+
+thread_local! {
+    static POLLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/* spins until cancelled, giving up after a while */
+fn cmd_spin(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    for i in 0..1000 {
+        if io.is_cancelled() {
+            POLLS.with(|polls| polls.set(i));
+            return 1;
+        }
+    }
+    0
+}
+
+fn cmd_ok(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    writeln!(io, "ok");
+    0
+}
+
+fn cmd_panic(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    writeln!(io, "before");
+    panic!("{} failed", argc[1]);
+}
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(40, 10)
+        .command("spin", cmd_spin)
+        .command("ok", cmd_ok)
+        .command("panic", cmd_panic)
+        .build()
+}
+
+#[test]
+fn abandons_the_line_at_the_prompt() {
+    let mut shell = shell();
+    shell.terminal().feed_str("abc");
+    shell.terminal().feed_key(Key::ctrl('c'));
+    shell.terminal().feed_str("ok\r");
+
+    assert_eq!(shell.listen(), Input::Interrupted);
+    assert_eq!(shell.listen().as_line(), Some("ok"));
+    assert_eq!(shell.terminal().line(0), "shell > abc^C");
+    assert_eq!(shell.terminal().line(1), "shell > ok");
+    assert_eq!(shell.last_status(), STATUS_INTERRUPTED);
+}
+
+#[test]
+fn exit_on_interrupt() {
+    let mut shell = shell();
+    shell.set_exit_on_interrupt(true);
+    shell.terminal().feed_str("abc\x03ok\r");
+
+    assert_eq!(shell.run(), STATUS_INTERRUPTED);
+    assert_eq!(shell.terminal().line(0), "shell > abc^C");
+    assert_eq!(shell.terminal().pending_input(), 3);
+}
+
+#[test]
+fn cancels_the_running_command_and_the_rest_of_the_line() {
+    let mut shell = shell();
+
+    /* typed while the command runs, the interrupt drops what came before it */
+    shell.terminal().feed_str("x\x03y\r");
+    assert_eq!(shell.parse("spin; ok"), STATUS_INTERRUPTED);
+    assert_eq!(POLLS.with(Cell::get), 0);
+    assert_eq!(shell.terminal().line(0), "^C");
+
+    assert_eq!(shell.listen().as_line(), Some("y"));
+
+    /* the next line starts over */
+    assert_eq!(shell.parse("ok"), 0);
+    assert_eq!(shell.terminal().line(2), "ok");
+}
+
+/* waits up to a second for the token */
+fn cmd_wait(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    for _ in 0..100 {
+        if io.is_cancelled() {
+            return 1;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    0
+}
+
+#[test]
+fn token_can_be_raised_elsewhere() {
+    let mut shell = shell();
+    shell.add_command("wait", cmd_wait);
+
+    let token = shell.cancel_token();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        token.cancel();
+    });
+
+    assert_eq!(shell.parse("wait || ok"), STATUS_INTERRUPTED);
+    canceller.join().unwrap();
+    assert_eq!(shell.terminal().screen()[..2], ["^C", ""]);
+    assert!(!shell.cancel_token().is_cancelled());
+}
+
+/* panics are only caught with `std` */
+#[cfg(feature = "std")]
+#[test]
+fn panicking_command_is_reported() {
+    let mut shell = shell();
+
+    assert_eq!(shell.parse("panic x || ok"), 0);
+    assert_eq!(shell.terminal().line(0), "before");
+    assert_eq!(shell.terminal().line(1), "panic: panicked: x failed.");
+    assert_eq!(shell.terminal().line(2), "ok");
+}
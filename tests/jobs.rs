@@ -0,0 +1,158 @@
+mod common;
+
+use rust_shell::tiny_shell::{
+    split_background, split_chain, ChainOp, Input, Io, ParseError, Shell, VirtualTerminal,
+    STATUS_INTERRUPTED, STATUS_STOPPED,
+};
+use std::time::Duration;
+
+fn cmd_slow(argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    std::thread::sleep(Duration::from_millis(50));
+    writeln!(io, "{} done", argc[0]);
+    argc.len() as i32 - 1
+}
+
+fn cmd_wait(_argc: &[&str], _argv: usize, io: &mut Io) -> i32 {
+    for _ in 0..200 {
+        if io.is_cancelled() {
+            writeln!(io, "cancelled");
+            return 5;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    0
+}
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(60, 20)
+        .command("slow", cmd_slow)
+        .command("wait", cmd_wait)
+        .external()
+        .build()
+}
+
+fn screen(shell: &mut Shell<VirtualTerminal>) -> Vec<String> {
+    let mut screen = shell.terminal().screen();
+    while screen.last().is_some_and(|line| line.is_empty()) {
+        screen.pop();
+    }
+    screen
+}
+
+#[test]
+fn ampersand_ends_a_command() {
+    let cmds: Vec<_> = split_chain("a & b '&' && c&")
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        cmds,
+        [
+            (None, "a &"),
+            (Some(ChainOp::Seq), " b '&' "),
+            (Some(ChainOp::And), " c&")
+        ]
+    );
+    assert_eq!(split_background("a | b &"), ("a | b ", true));
+    assert_eq!(split_background("a '&'"), ("a '&'", false));
+
+    assert_eq!(
+        split_chain("& a").next(),
+        Some(Err(ParseError::UnexpectedAmpersand))
+    );
+    assert_eq!(
+        split_chain("a & & b").nth(1),
+        Some(Err(ParseError::UnexpectedAmpersand))
+    );
+}
+
+#[test]
+fn commands_run_on_threads() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("slow a &"), 0);
+    assert_eq!(shell.parse("slow a b &"), 0);
+    shell.parse("jobs");
+    assert_eq!(
+        screen(&mut shell),
+        [
+            "[1]",
+            "[2]",
+            "[1]- Running  slow a",
+            "[2]+ Running  slow a b"
+        ]
+    );
+
+    /* fg waits for the job and shows what it printed */
+    assert_eq!(shell.parse("fg %1"), 1);
+    assert_eq!(screen(&mut shell)[4..], ["slow a", "slow done"]);
+
+    /* the other one is reported before the next prompt */
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        screen(&mut shell)[6..],
+        ["slow done", "[2]+ Exit 2   slow a b", "shell >"]
+    );
+    assert_eq!(shell.parse("fg"), 1);
+}
+
+#[test]
+fn fg_keeps_the_typing_and_passes_ctrl_c_to_a_thread() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("slow a &"), 0);
+    shell.terminal().feed_str("ls");
+    assert_eq!(shell.parse("fg"), 1);
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell > ls");
+
+    let mut shell = self::shell();
+    assert_eq!(shell.parse("wait &"), 0);
+    shell.terminal().feed_str("x\x03");
+    assert_eq!(shell.parse("fg"), STATUS_INTERRUPTED);
+    assert_eq!(screen(&mut shell)[1..], ["wait", "cancelled", "^C"]);
+
+    /* the interrupt drops what was typed before it */
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().cursor_line(), "shell >");
+}
+
+#[test]
+fn stopped_programs_continue_with_bg_and_fg() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("sh -c 'kill -STOP $$; exit 4'"), STATUS_STOPPED);
+    shell.parse("jobs");
+    assert_eq!(
+        screen(&mut shell),
+        [
+            "",
+            "[1]+ Stopped  sh -c kill -STOP $$; exit 4",
+            "[1]+ Stopped  sh -c kill -STOP $$; exit 4"
+        ]
+    );
+
+    assert_eq!(shell.parse("bg 1"), 0);
+    assert_eq!(shell.parse("fg"), 4);
+    assert_eq!(shell.parse("jobs"), 0);
+    assert_eq!(
+        screen(&mut shell)[3..],
+        [
+            "[1] sh -c kill -STOP $$; exit 4 &",
+            "sh -c kill -STOP $$; exit 4"
+        ]
+    );
+}
+
+#[test]
+fn only_commands_and_programs_go_to_the_background() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("set X 1 &"), 1);
+    assert_eq!(shell.parse("slow | slow &"), 1);
+    assert_eq!(shell.parse("bg"), 1);
+    assert_eq!(
+        screen(&mut shell),
+        [
+            "only commands and programs can run in the background.",
+            "pipelines cannot run in the background.",
+            "bg: no such job."
+        ]
+    );
+}
@@ -0,0 +1,61 @@
+use rust_shell::tiny_shell::{Key, KeyCode, KeyDecoder, Modifiers};
+
+fn decode(bytes: &[u8]) -> Vec<Key> {
+    let mut decoder = KeyDecoder::new();
+    let mut keys: Vec<Key> = bytes.iter().filter_map(|c| decoder.feed(*c)).collect();
+    keys.extend(decoder.timeout());
+    keys
+}
+
+#[test]
+fn csi_and_ss3_forms() {
+    assert_eq!(
+        decode(b"\x1b[A\x1bOH\x1b[4~\x1b[3~"),
+        [
+            Key::new(KeyCode::Up),
+            Key::new(KeyCode::Home),
+            Key::new(KeyCode::End),
+            Key::new(KeyCode::Delete),
+        ]
+    );
+}
+
+#[test]
+fn modifiers() {
+    assert_eq!(
+        decode(b"\x1b[1;5D\x1b[Z\x1bf\x01"),
+        [
+            Key::with_mods(KeyCode::Left, Modifiers::CTRL),
+            Key::with_mods(KeyCode::Tab, Modifiers::SHIFT),
+            Key::alt('f'),
+            Key::ctrl('a'),
+        ]
+    );
+}
+
+#[test]
+fn bare_escape_and_garbage() {
+    assert_eq!(decode(b"\x1b"), [Key::new(KeyCode::Escape)]);
+    assert_eq!(decode(b"\x1b[200;1;2;3;4;5x"), [Key::new(KeyCode::Unknown)]);
+    assert_eq!(decode("é".as_bytes()), [Key::new(KeyCode::Char('é'))]);
+}
+
+#[test]
+fn telnet_window_size() {
+    assert_eq!(
+        decode(b"\xff\xfa\x1f\x00\x50\x00\x18\xff\xf0a"),
+        [
+            Key::new(KeyCode::Resize(80, 24)),
+            Key::new(KeyCode::Char('a'))
+        ]
+    );
+
+    /* a 255 in the payload is doubled, other negotiations are swallowed */
+    assert_eq!(
+        decode(b"\xff\xfa\x1f\x01\xff\xff\x00\x18\xff\xf0\xff\xfb\x01"),
+        [
+            Key::new(KeyCode::Resize(511, 24)),
+            Key::new(KeyCode::Unknown)
+        ]
+    );
+}
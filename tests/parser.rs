@@ -0,0 +1,102 @@
+use rust_shell::tiny_shell::{parse_args, Io, Line, ParseError, Shell, VirtualTerminal};
+use std::cell::RefCell;
+
+fn vars(name: &str, out: &mut Line) {
+    match name {
+        "NAME" => out.push_str("rust shell"),
+        "?" => out.push('3'),
+        _ => {}
+    }
+}
+
+fn parse(cmd: &str) -> Result<Vec<String>, ParseError> {
+    parse_args(cmd, vars).map(|args| args.iter().map(String::from).collect())
+}
+
+#[test]
+fn quoting_and_escapes() {
+    assert_eq!(
+        parse("  echo  a\\ b 'c  d' \"e\\\"f\" # comment").unwrap(),
+        ["echo", "a b", "c  d", "e\"f"]
+    );
+    assert_eq!(parse("echo '' x#y").unwrap(), ["echo", "", "x#y"]);
+    assert_eq!(parse("echo 'open"), Err(ParseError::UnterminatedQuote));
+    assert_eq!(parse("echo \"open"), Err(ParseError::UnterminatedQuote));
+}
+
+#[test]
+fn variable_expansion() {
+    assert_eq!(
+        parse("echo $NAME ${NAME}! \"[$NAME]\" '$NAME'").unwrap(),
+        ["echo", "rust shell", "rust shell!", "[rust shell]", "$NAME"]
+    );
+    assert_eq!(
+        parse("echo $? $UNSET \"$UNSET\" $ \\$NAME").unwrap(),
+        ["echo", "3", "", "$", "$NAME"]
+    );
+    assert_eq!(parse("echo ${NAME"), Err(ParseError::BadSubstitution));
+    assert_eq!(parse("echo ${1x}"), Err(ParseError::BadSubstitution));
+}
+
+thread_local! {
+    static ARGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn recorded() -> Vec<String> {
+    ARGS.with(|args| args.borrow().clone())
+}
+
+fn cmd_record(argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    ARGS.with(|args| *args.borrow_mut() = argc[1..].iter().map(|arg| arg.to_string()).collect());
+    argc.len() as i32 - 1
+}
+
+#[test]
+fn set_unset_and_last_status() {
+    let mut shell = Shell::new("shell > ", VirtualTerminal::new(40, 10));
+    shell.add_command("record", cmd_record);
+
+    assert_eq!(shell.parse("set GREETING hello  world"), 0);
+    assert_eq!(shell.parse("record \"$GREETING\" $?"), 2);
+    assert_eq!(recorded(), ["hello world", "0"]);
+
+    assert_eq!(shell.parse("record $?"), 1);
+    assert_eq!(recorded(), ["2"]);
+
+    assert_eq!(shell.parse("unset GREETING"), 0);
+    assert_eq!(shell.vars().get("GREETING"), None);
+    assert_eq!(shell.parse("set 1abc x"), 1);
+    assert_eq!(shell.terminal().line(0), "set: invalid variable name.");
+
+    shell.parse("set A 1");
+    shell.parse("set B 2");
+    shell.parse("set");
+    assert_eq!(shell.terminal().line(1), "A=1");
+    assert_eq!(shell.terminal().line(2), "B=2");
+}
+
+/* the environment is only read with `std` */
+#[cfg(feature = "std")]
+#[test]
+fn environment_is_visible() {
+    std::env::set_var("RUST_SHELL_TEST_ENV", "from env");
+    let mut shell = Shell::new("shell > ", VirtualTerminal::new(40, 10));
+    shell.add_command("record", cmd_record);
+
+    shell.parse("record $RUST_SHELL_TEST_ENV");
+    assert_eq!(recorded(), ["from env"]);
+
+    /* shell variables shadow the environment without modifying it */
+    shell.parse("set RUST_SHELL_TEST_ENV shell");
+    shell.parse("record $RUST_SHELL_TEST_ENV");
+    assert_eq!(recorded(), ["shell"]);
+    assert_eq!(std::env::var("RUST_SHELL_TEST_ENV").unwrap(), "from env");
+}
+
+#[test]
+fn backslash_newline_joins_lines() {
+    assert_eq!(
+        parse("echo a\\\nb \\\n\"c\\\nd\" 'e\nf'").unwrap(),
+        ["echo", "ab", "cd", "e\nf"]
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{captured, cmd_capture, cmd_echo, cmd_lines};
+use rust_shell::tiny_shell::{split_pipeline, ParseError, Shell, VirtualTerminal};
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(60, 10)
+        .command("echo", cmd_echo)
+        .command("lines", cmd_lines)
+        .command("capture", cmd_capture)
+        .filters()
+        .build()
+}
+
+fn split(cmd: &str) -> Result<Vec<&str>, ParseError> {
+    split_pipeline(cmd).collect()
+}
+
+#[test]
+fn splits_stages() {
+    assert_eq!(
+        split("a | 'b|c' \"|\" | d\\|e").unwrap(),
+        ["a ", " 'b|c' \"|\" ", " d\\|e"]
+    );
+    assert_eq!(split("a || b").unwrap(), ["a "]);
+    assert_eq!(split("| a"), Err(ParseError::UnexpectedPipe));
+    assert_eq!(split("a | | b"), Err(ParseError::UnexpectedPipe));
+    assert_eq!(split("a |"), Err(ParseError::UnexpectedEnd));
+
+    let mut shell = shell();
+    assert_eq!(shell.parse("echo a | ; echo b"), 2);
+    assert_eq!(
+        shell.terminal().line(0),
+        "syntax error: unexpected end of line."
+    );
+}
+
+#[test]
+fn output_feeds_the_next_command() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("lines one two | capture"), 0);
+    assert_eq!(captured().as_deref(), Some("one\ntwo\n"));
+
+    /* only the last stage prints to the terminal */
+    assert_eq!(shell.parse("capture"), 0);
+    assert_eq!(captured(), None);
+    assert_eq!(shell.terminal().cursor(), (0, 0));
+
+    shell.parse("lines b a | sort -r | head -n 1");
+    assert_eq!(shell.terminal().screen()[..2], ["b", ""]);
+}
+
+#[test]
+fn filters() {
+    let mut shell = shell();
+    let mut run = |cmd: &str| {
+        shell.parse(&format!("{} | capture", cmd));
+        captured().unwrap()
+    };
+
+    assert_eq!(run("lines apple Berry cherry | grep -i b"), "Berry\n");
+    assert_eq!(run("lines apple Berry cherry | grep -v rr"), "apple\n");
+    assert_eq!(run("lines apple Berry cherry | grep -c e"), "3\n");
+    assert_eq!(run("lines 1 2 3 4 | head -2"), "1\n2\n");
+    assert_eq!(run("lines 1 2 3 4 | tail -n 3"), "2\n3\n4\n");
+    assert_eq!(run("lines 10 9 b 9 | sort -n -u"), "b\n9\n10\n");
+    assert_eq!(run("lines c a b | sort"), "a\nb\nc\n");
+    assert_eq!(run("echo 'two words' | wc"), "1 2 10\n");
+    assert_eq!(run("lines a b | wc -l"), "2\n");
+}
+
+#[test]
+fn pipeline_status_and_builtins() {
+    let mut shell = shell();
+    assert_eq!(shell.parse("lines a | grep b"), 1);
+    assert_eq!(shell.parse("lines a | grep b || echo none"), 0);
+    assert_eq!(shell.terminal().line(0), "none");
+
+    shell.parse("alias hi='grep h'");
+    shell.parse("help | hi | capture");
+    assert_eq!(
+        captured().as_deref(),
+        Some("help\nhistory\necho\nhead\nhi='grep h'\n")
+    );
+}
@@ -0,0 +1,61 @@
+use rust_shell::tiny_shell::{Input, Shell, VirtualTerminal};
+
+#[test]
+fn messages_are_printed_above_the_input() {
+    let mut shell = Shell::new("shell > ", VirtualTerminal::new(40, 10));
+    let printer = shell.external_printer();
+    shell.terminal().feed_str("ec");
+    assert_eq!(shell.listen(), Input::Eof);
+
+    std::thread::spawn(move || {
+        printer.print("device 1 connected");
+        printer.print("rx: 2 lines\nrx: done\n");
+    })
+    .join()
+    .unwrap();
+
+    shell.terminal().feed_str("ho");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().screen()[..5],
+        [
+            "device 1 connected",
+            "rx: 2 lines",
+            "rx: done",
+            "shell > echo",
+            ""
+        ]
+    );
+    assert_eq!(shell.terminal().cursor(), (3, 12));
+}
+
+#[test]
+fn status_and_prompt_change_from_another_thread() {
+    let mut shell = Shell::new("shell > ", VirtualTerminal::new(40, 5));
+    shell.set_right_prompt("[$RUST_SHELL_TEST_LINK]");
+    let status = shell.status_handle();
+    std::env::set_var("RUST_SHELL_TEST_LINK", "down");
+    shell.terminal().feed_str("ec");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().line(0),
+        "shell > ec                        [down]"
+    );
+
+    std::thread::spawn(move || {
+        std::env::set_var("RUST_SHELL_TEST_LINK", "up");
+        status.set_status(Some("uart0 online"));
+        status.refresh_prompt();
+    })
+    .join()
+    .unwrap();
+
+    shell.terminal().feed_str("ho");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(
+        shell.terminal().line(0),
+        "shell > echo                        [up]"
+    );
+    assert_eq!(shell.terminal().line(4), "uart0 online");
+    assert_eq!(shell.terminal().cursor(), (0, 12));
+}
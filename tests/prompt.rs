@@ -0,0 +1,92 @@
+use rust_shell::tiny_shell::{Input, Shell, VirtualTerminal};
+
+#[test]
+fn variables_and_status_expand_before_every_line() {
+    let mut shell = Shell::new("[$DEVICE:$?] > ", VirtualTerminal::new(40, 10));
+    shell.vars().set("DEVICE", "uart0");
+    shell.terminal().feed_str("nope\r");
+
+    let cmd = shell.listen();
+    shell.parse(cmd.as_line().unwrap());
+    shell.vars().set("DEVICE", "uart1");
+    assert_eq!(shell.listen(), Input::Eof);
+
+    assert_eq!(shell.terminal().line(0), "[uart0:0] > nope");
+    assert_eq!(shell.terminal().line(1), "unknown command.");
+    assert_eq!(shell.terminal().line(2), "[uart1:127] >");
+}
+
+#[test]
+fn colors_take_no_room() {
+    let mut shell = Shell::new("\\e[1;32mok\\e[0m\\\\ ", VirtualTerminal::new(10, 5));
+    shell.terminal().feed_str("abcdefg");
+    assert_eq!(shell.listen(), Input::Eof);
+
+    assert!(shell
+        .terminal()
+        .output()
+        .starts_with(b"\x1b[1;32mok\x1b[0m\\ "));
+    assert_eq!(shell.terminal().screen()[..2], ["ok\\ abcdef", "g"]);
+    assert_eq!(shell.terminal().cursor(), (1, 1));
+}
+
+#[test]
+fn set_prompt_changes_the_template() {
+    let mut shell = Shell::new("> ", VirtualTerminal::new(40, 10));
+    shell.set_prompt("\\$ ");
+    assert_eq!(shell.prompt(), "\\$ ");
+
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "$");
+}
+
+#[test]
+fn right_prompt_hides_when_the_input_reaches_it() {
+    let mut shell = Shell::new("> ", VirtualTerminal::new(20, 5));
+    shell.set_right_prompt("[$?]");
+    shell.terminal().feed_str("abc");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "> abc            [0]");
+    assert_eq!(shell.terminal().cursor(), (0, 5));
+
+    shell.terminal().feed_str("defghijklmn");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "> abcdefghijklmn [0]");
+
+    shell.terminal().feed_str("o");
+    assert_eq!(shell.listen(), Input::Eof);
+    assert_eq!(shell.terminal().line(0), "> abcdefghijklmno");
+    assert_eq!(shell.terminal().cursor(), (0, 17));
+}
+
+#[test]
+fn status_line_stays_on_the_bottom_row() {
+    let mut shell = Shell::new("> ", VirtualTerminal::new(20, 4));
+    shell.set_status(Some("uart0 online"));
+    shell.terminal().feed_str("a\rb\rc\r");
+    while let Input::Line(cmd) = shell.listen() {
+        shell.parse(&cmd);
+    }
+    assert_eq!(
+        shell.terminal().screen(),
+        ["> c", "unknown command.", ">", "uart0 online"]
+    );
+
+    shell.set_status(Some("uart0 offline"));
+    assert_eq!(shell.terminal().line(3), "uart0 offline");
+    assert_eq!(shell.terminal().cursor(), (2, 2));
+
+    shell.set_status(None);
+    assert_eq!(shell.terminal().line(3), "");
+}
+
+#[test]
+fn status_line_keeps_the_cursor_column() {
+    let mut shell = Shell::new("shell > ", VirtualTerminal::new(40, 5));
+    shell.terminal().feed_str("echo");
+    assert_eq!(shell.listen(), Input::Eof);
+
+    shell.set_status(Some("uart0 online"));
+    assert_eq!(shell.terminal().cursor(), (0, 12));
+    assert_eq!(shell.terminal().line(4), "uart0 online");
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::cmd_lines;
+use rust_shell::tiny_shell::{parse_args, Line, ParseError, Redirect, Shell, VirtualTerminal};
+use std::fs;
+use std::path::PathBuf;
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(120, 10)
+        .command("lines", cmd_lines)
+        .filters()
+        .build()
+}
+
+/* fresh scratch directory per test */
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rust_shell_redirect_{}_{}",
+        std::process::id(),
+        name
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn no_vars(_name: &str, _out: &mut Line) {}
+
+#[test]
+fn parses_redirections() {
+    let args = parse_args("sort<in -r >'out file'>>log", no_vars).unwrap();
+    assert_eq!(args.iter().collect::<Vec<_>>(), ["sort", "-r"]);
+    assert_eq!(
+        args.redirects().collect::<Vec<_>>(),
+        [
+            (Redirect::Input, "in"),
+            (Redirect::Output, "out file"),
+            (Redirect::Append, "log")
+        ]
+    );
+
+    /* quoted or escaped they are plain text */
+    let args = parse_args("lines '>' \\<", no_vars).unwrap();
+    assert_eq!(args.iter().collect::<Vec<_>>(), ["lines", ">", "<"]);
+    assert_eq!(args.redirects().count(), 0);
+
+    assert_eq!(
+        parse_args("lines >", no_vars).err(),
+        Some(ParseError::MissingRedirectTarget)
+    );
+    assert_eq!(
+        parse_args("lines > < in", no_vars).err(),
+        Some(ParseError::MissingRedirectTarget)
+    );
+}
+
+#[test]
+fn output_to_files_and_back() {
+    let dir = scratch("files");
+    let file = dir.join("out.txt");
+    let file = file.to_str().unwrap();
+    let mut shell = shell();
+
+    assert_eq!(shell.parse(&format!("lines b a > {}", file)), 0);
+    assert_eq!(shell.parse(&format!("lines c >> {}", file)), 0);
+    assert_eq!(fs::read_to_string(file).unwrap(), "b\na\nc\n");
+
+    /* nothing reaches the screen, the output went to the file */
+    assert_eq!(shell.terminal().cursor(), (0, 0));
+
+    assert_eq!(
+        shell.parse(&format!("sort < {} | head -n 1 > {}.head", file, file)),
+        0
+    );
+    assert_eq!(fs::read_to_string(format!("{}.head", file)).unwrap(), "a\n");
+
+    shell.parse(&format!("lines b a > {}", file));
+    assert_eq!(
+        shell.parse(&format!("sort < {} > {}.sorted", file, file)),
+        0
+    );
+    assert_eq!(
+        fs::read_to_string(format!("{}.sorted", file)).unwrap(),
+        "a\nb\n"
+    );
+
+    /* builtins print through the same streams */
+    shell.parse(&format!("help > {}", file));
+    assert!(fs::read_to_string(file).unwrap().starts_with("alias\n"));
+}
+
+#[test]
+fn redirection_errors_fail_the_command() {
+    let dir = scratch("errors");
+    let mut shell = shell();
+
+    let missing = dir.join("missing.txt");
+    assert_eq!(shell.parse(&format!("sort < {}", missing.display())), 1);
+    assert!(shell
+        .terminal()
+        .line(0)
+        .starts_with(&format!("{}: ", missing.display())));
+
+    let no_dir = dir.join("nodir").join("out.txt");
+    assert_eq!(
+        shell.parse(&format!("lines a > {} || lines failed", no_dir.display())),
+        0
+    );
+    assert!(!no_dir.exists());
+    assert_eq!(shell.terminal().line(2), "failed");
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use rust_shell::tiny_shell::{Io, ScriptOptions, Shell, VirtualTerminal, STATUS_UNKNOWN_CMD};
+use std::io::Cursor;
+
+fn cmd_ok(_argc: &[&str], _argv: usize, _io: &mut Io) -> i32 {
+    0
+}
+
+fn cmd_fail(_argc: &[&str], argv: usize, _io: &mut Io) -> i32 {
+    argv as i32
+}
+
+fn shell() -> Shell<'static, VirtualTerminal> {
+    common::shell(120, 10)
+        .command("ok", cmd_ok)
+        .command("fail", cmd_fail)
+        .build()
+}
+
+const SCRIPT: &str = "# setup\nok\n\nfail now\nnope\n";
+
+#[test]
+fn runs_every_line_and_returns_last_status() {
+    let mut shell = shell();
+    let status = shell.run_script(Cursor::new(SCRIPT), ScriptOptions::default());
+
+    assert_eq!(status.unwrap(), STATUS_UNKNOWN_CMD);
+    assert_eq!(shell.terminal().line(0), "unknown command.");
+}
+
+#[test]
+fn stop_on_error_with_echo() {
+    let mut shell = shell();
+    let opts = ScriptOptions {
+        echo: true,
+        stop_on_error: true,
+    };
+    let status = shell.run_script(Cursor::new(SCRIPT), opts);
+
+    assert_eq!(status.unwrap(), 2);
+    assert_eq!(shell.terminal().line(0), "shell > ok");
+    assert_eq!(shell.terminal().line(1), "shell > fail now");
+    assert_eq!(shell.terminal().line(2), "");
+}
+
+#[test]
+fn exit_ends_the_script() {
+    let mut shell = shell();
+    let status = shell.run_script(Cursor::new("ok\nexit 5\nnope\n"), ScriptOptions::default());
+
+    assert_eq!(status.unwrap(), 5);
+    assert_eq!(shell.exit_status(), Some(5));
+    assert_eq!(shell.terminal().line(0), "");
+}
+
+#[test]
+fn missing_script_file() {
+    let mut shell = shell();
+    assert!(shell
+        .run_file("/nonexistent/script", ScriptOptions::default())
+        .is_err());
+}
+
+fn write_script(name: &str, content: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("rust_shell_{}_{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn source_reports_file_and_line() {
+    let inner = write_script("inner", "ok\nnope\n");
+    let outer = write_script("outer", &format!("ok\nsource {}\nfail\n", inner.display()));
+
+    let mut shell = shell();
+    shell
+        .terminal()
+        .feed_str(&format!("source {}\r", outer.display()));
+    let cmd = shell.listen().as_line().unwrap().to_string();
+
+    assert_eq!(shell.parse(&cmd), 1);
+    assert_eq!(
+        shell.terminal().line(1),
+        format!("{}:2: unknown command.", inner.display())
+    );
+    assert_eq!(shell.terminal().line(2), "");
+}
+
+#[test]
+fn source_errors() {
+    let looping = write_script("loop", "");
+    std::fs::write(&looping, format!("source {}\n", looping.display())).unwrap();
+
+    let mut shell = shell();
+    assert_eq!(shell.parse("source"), 2);
+    assert_eq!(shell.parse("source /nonexistent/rc"), 1);
+    assert_eq!(shell.parse(&format!("source {}", looping.display())), 1);
+    assert!(shell
+        .terminal()
+        .line(2)
+        .ends_with(":1: source: nested too deeply."));
+    assert_eq!(shell.load_rc("/nonexistent/rc"), 0);
+}
+
+#[test]
+fn piped_lines_arriving_while_a_command_runs_are_kept() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_shell"))
+        .arg("--norc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"sleep 0.3\n").unwrap();
+    stdin.flush().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    stdin.write_all(b"echo c\necho d\n").unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("c\n") && stdout.contains("d\n"),
+        "{:?}",
+        stdout
+    );
+}
+
+#[test]
+fn piped_output_has_plain_line_feeds_and_errors_go_to_stderr() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust_shell"))
+        .arg("--norc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"echo hi\nnosuch\necho a b\n").unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\na b\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "unknown command.\n"
+    );
+}